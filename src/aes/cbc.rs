@@ -1,29 +1,45 @@
 use xor;
-use result::Result;
-use super::ecb;
+use result::{Result, CrackError};
+use super::{Aes, BlockCipher};
 
 pub fn decrypt(key: &[u8], iv: &[u8], input: &[u8]) -> Result<Vec<u8>> {
-  let mut prev = Vec::from(iv);
+  decrypt_with(&try!(Aes::new(key)), iv, input)
+}
+
+pub fn encrypt(key: &[u8], iv: &[u8], input: &[u8]) -> Result<Vec<u8>> {
+  encrypt_with(&try!(Aes::new(key)), iv, input)
+}
+
+pub fn decrypt_with<C: BlockCipher + ?Sized>(cipher: &C, iv: &[u8], input: &[u8]) -> Result<Vec<u8>> {
+  let bs = cipher.block_size();
+  if iv.len() != bs || !input.len().is_multiple_of(bs) {
+    return Err(CrackError::Str("CBC needs a one block IV and whole blocks of input"));
+  }
+  let mut prev = iv;
   let mut acc = vec![];
+  let mut decrypted = vec![0u8; bs];
 
-  for block in input.chunks(16) {
-    let ecb_decrypt = try!(ecb::decrypt(key, block));
-    let next: Vec<u8> = xor::xor_iters(prev, ecb_decrypt);
+  for block in input.chunks(bs) {
+    cipher.decrypt_block(block, &mut decrypted);
+    let next: Vec<u8> = xor::xor_iters(prev, &decrypted);
     acc.extend_from_slice(&next);
-    prev = Vec::from(block);
+    prev = block;
   }
   Ok(acc)
 }
 
-pub fn encrypt(key: &[u8], iv: &[u8], input: &[u8]) -> Result<Vec<u8>> {
+pub fn encrypt_with<C: BlockCipher + ?Sized>(cipher: &C, iv: &[u8], input: &[u8]) -> Result<Vec<u8>> {
+  let bs = cipher.block_size();
+  if iv.len() != bs || !input.len().is_multiple_of(bs) {
+    return Err(CrackError::Str("CBC needs a one block IV and whole blocks of input"));
+  }
   let mut prev = Vec::from(iv);
   let mut acc = vec![];
 
-  for block in input.chunks(16) {
-    let mixed: Vec<u8> = xor::xor_iters(prev.to_vec(), block.to_vec());
-    let next = try!(ecb::encrypt(key, &mixed));
-    acc.extend_from_slice(&next);
-    prev = Vec::from(next);
+  for block in input.chunks(bs) {
+    let mixed: Vec<u8> = xor::xor_iters(&prev, block);
+    cipher.encrypt_block(&mixed, &mut prev);
+    acc.extend_from_slice(&prev);
   }
   Ok(acc)
 }
@@ -42,4 +58,13 @@ mod test {
     println!("{} {:?}", crypted.len(), crypted);
     assert_eq!(message, decrypt(key, iv, &crypted).unwrap().as_slice())
   }
+
+  #[test]
+  fn aes256() {
+    let key = "YELLOW SUBMARINEYELLOW SUBMARINE".as_bytes();
+    let iv = &[7; 16][..];
+    let message = "Attack the castle gates from the high west wall.".as_bytes();
+    let crypted = encrypt(key, iv, message).unwrap();
+    assert_eq!(message, decrypt(key, iv, &crypted).unwrap().as_slice())
+  }
 }
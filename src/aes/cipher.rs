@@ -0,0 +1,99 @@
+use crypto::aessafe::{AesSafe128Encryptor, AesSafe128Decryptor, AesSafe192Encryptor,
+                     AesSafe192Decryptor, AesSafe256Encryptor, AesSafe256Decryptor};
+use crypto::symmetriccipher::{BlockEncryptor, BlockDecryptor};
+use result::{Result, CrackError};
+
+/// A keyed block cipher: transforms exactly one block at a time.
+///
+/// The modes in `aes::ecb` and `aes::cbc` are written against this trait, so anything that can
+/// encrypt and decrypt a single block can be driven by them.
+pub trait BlockCipher {
+  fn block_size(&self) -> usize;
+  fn key_size(&self) -> usize;
+  fn encrypt_block(&self, input: &[u8], output: &mut [u8]);
+  fn decrypt_block(&self, input: &[u8], output: &mut [u8]);
+}
+
+/// AES keyed with a 16, 24 or 32 byte key - the variant is chosen from the key length.
+pub struct Aes {
+  key_size: usize,
+  encryptor: Box<dyn BlockEncryptor>,
+  decryptor: Box<dyn BlockDecryptor>,
+}
+
+impl Aes {
+  pub fn new(key: &[u8]) -> Result<Aes> {
+    let (encryptor, decryptor): (Box<dyn BlockEncryptor>, Box<dyn BlockDecryptor>) =
+      match key.len() {
+        16 => (Box::new(AesSafe128Encryptor::new(key)), Box::new(AesSafe128Decryptor::new(key))),
+        24 => (Box::new(AesSafe192Encryptor::new(key)), Box::new(AesSafe192Decryptor::new(key))),
+        32 => (Box::new(AesSafe256Encryptor::new(key)), Box::new(AesSafe256Decryptor::new(key))),
+        _ => return Err(CrackError::Str("AES keys must be 16, 24 or 32 bytes")),
+      };
+    Ok(Aes {
+      key_size: key.len(),
+      encryptor: encryptor,
+      decryptor: decryptor,
+    })
+  }
+}
+
+impl BlockCipher for Aes {
+  fn block_size(&self) -> usize {
+    16
+  }
+
+  fn key_size(&self) -> usize {
+    self.key_size
+  }
+
+  fn encrypt_block(&self, input: &[u8], output: &mut [u8]) {
+    self.encryptor.encrypt_block(input, output)
+  }
+
+  fn decrypt_block(&self, input: &[u8], output: &mut [u8]) {
+    self.decryptor.decrypt_block(input, output)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use byte_convert::hex2bytes;
+
+  // FIPS-197 Appendix C: key is 00 01 02 ..., plaintext is 00 11 22 ... ff
+  fn fips_vector(key_size: u8, expected: &str) {
+    let key: Vec<u8> = (0..key_size).collect();
+    let plain: Vec<u8> = (0..16).map(|n| n * 0x11).collect();
+    let cipher = Aes::new(&key).unwrap();
+    assert_eq!(cipher.key_size(), key_size as usize);
+    assert_eq!(cipher.block_size(), 16);
+    let mut crypted = [0u8; 16];
+    cipher.encrypt_block(&plain, &mut crypted);
+    assert_eq!(&crypted[..], &hex2bytes(expected).unwrap()[..]);
+
+    let mut decrypted = [0u8; 16];
+    cipher.decrypt_block(&crypted, &mut decrypted);
+    assert_eq!(&decrypted[..], &plain[..]);
+  }
+
+  #[test]
+  fn aes128() {
+    fips_vector(16, "69c4e0d86a7b0430d8cdb78070b4c55a");
+  }
+
+  #[test]
+  fn aes192() {
+    fips_vector(24, "dda97ca4864cdfe06eaf70a0ec0d7191");
+  }
+
+  #[test]
+  fn aes256() {
+    fips_vector(32, "8ea2b7ca516745bfeafc49904b496089");
+  }
+
+  #[test]
+  fn rejects_odd_keys() {
+    assert!(Aes::new(&[0; 20]).is_err());
+  }
+}
@@ -0,0 +1,255 @@
+use result::{Result, CrackError};
+use super::{Aes, BlockCipher};
+
+/// Lays out the counter block fed to the cipher for a given block index.
+///
+/// CTR mode never runs the cipher backwards, so the only thing that differs between the common
+/// flavours is how the nonce and the block count are packed into the 16 byte input block.
+pub trait CounterFormat {
+  fn nonce_size(&self) -> usize;
+  /// `nonce` is always `nonce_size()` bytes.
+  fn counter_block(&self, nonce: &[u8], index: u64) -> [u8; 16];
+}
+
+/// Cryptopals' layout: 64 bit nonce, then a 64 bit little-endian block count.
+#[derive(Debug, Clone, Copy)]
+pub struct LittleEndian64;
+
+/// NIST SP 800-38A/GCM layout: 96 bit nonce, then a 32 bit big-endian counter that wraps.
+#[derive(Debug, Clone, Copy)]
+pub struct BigEndian32;
+
+/// The whole 128 bit block is an initial counter value, incremented as a big-endian integer.
+#[derive(Debug, Clone, Copy)]
+pub struct Increment128;
+
+impl CounterFormat for LittleEndian64 {
+  fn nonce_size(&self) -> usize {
+    8
+  }
+
+  fn counter_block(&self, nonce: &[u8], index: u64) -> [u8; 16] {
+    let mut block = [0u8; 16];
+    block[..8].copy_from_slice(nonce);
+    for (n, byte) in block[8..].iter_mut().enumerate() {
+      *byte = (index >> (8 * n)) as u8;
+    }
+    block
+  }
+}
+
+impl CounterFormat for BigEndian32 {
+  fn nonce_size(&self) -> usize {
+    12
+  }
+
+  fn counter_block(&self, nonce: &[u8], index: u64) -> [u8; 16] {
+    let mut block = [0u8; 16];
+    block[..12].copy_from_slice(nonce);
+    for (n, byte) in block[12..].iter_mut().rev().enumerate() {
+      *byte = (index >> (8 * n)) as u8;
+    }
+    block
+  }
+}
+
+impl CounterFormat for Increment128 {
+  fn nonce_size(&self) -> usize {
+    16
+  }
+
+  fn counter_block(&self, nonce: &[u8], index: u64) -> [u8; 16] {
+    let mut block = [0u8; 16];
+    block.copy_from_slice(nonce);
+    let mut carry = index;
+    for byte in block.iter_mut().rev() {
+      if carry == 0 {
+        break;
+      }
+      let sum = *byte as u64 + (carry & 0xff);
+      *byte = sum as u8;
+      carry = (carry >> 8) + (sum >> 8);
+    }
+    block
+  }
+}
+
+/// CTR mode over any 16 byte block cipher, with the counter laid out by `F`.
+///
+/// # Examples
+/// ```
+/// use cryptopals::aes::Aes;
+/// use cryptopals::aes::ctr::{Ctr, BigEndian32};
+/// let key = "YELLOW SUBMARINE".as_bytes();
+/// let ctr = Ctr::new(Aes::new(key).unwrap(), BigEndian32, &[7; 12]).unwrap();
+/// let crypted = ctr.encrypt("Seek anywhere in the stream".as_bytes());
+/// assert_eq!(ctr.decrypt(&crypted), "Seek anywhere in the stream".as_bytes());
+/// assert_eq!(ctr.keystream(5, 4), ctr.keystream_block(0)[5..9].to_vec());
+/// ```
+pub struct Ctr<C, F> {
+  cipher: C,
+  format: F,
+  nonce: Vec<u8>,
+}
+
+impl Ctr<Aes, LittleEndian64> {
+  /// AES-CTR as Cryptopals uses it: the nonce is a little-endian u64.
+  pub fn cryptopals(key: &[u8], nonce: u64) -> Result<Ctr<Aes, LittleEndian64>> {
+    let nonce: Vec<u8> = (0..8).map(|n| (nonce >> (8 * n)) as u8).collect();
+    Ctr::new(try!(Aes::new(key)), LittleEndian64, &nonce)
+  }
+}
+
+impl<C: BlockCipher, F: CounterFormat> Ctr<C, F> {
+  pub fn new(cipher: C, format: F, nonce: &[u8]) -> Result<Ctr<C, F>> {
+    if cipher.block_size() != 16 || nonce.len() != format.nonce_size() {
+      return Err(CrackError::Str("CTR nonce doesn't fit the counter format"));
+    }
+    Ok(Ctr {
+      cipher: cipher,
+      format: format,
+      nonce: Vec::from(nonce),
+    })
+  }
+
+  pub fn keystream_block(&self, index: u64) -> [u8; 16] {
+    let mut out = [0u8; 16];
+    self.cipher.encrypt_block(&self.format.counter_block(&self.nonce, index), &mut out);
+    out
+  }
+
+  /// `len` bytes of keystream starting at byte `offset` - only the blocks that overlap the range
+  /// are generated.
+  pub fn keystream(&self, offset: usize, len: usize) -> Vec<u8> {
+    let mut stream = vec![0u8; len];
+    self.apply_in_place(offset, &mut stream);
+    stream
+  }
+
+  /// Encrypts or decrypts (they're the same operation) `input` as though it sat at byte `offset`
+  /// of the stream.
+  pub fn apply(&self, offset: usize, input: &[u8]) -> Vec<u8> {
+    let mut output = input.to_vec();
+    self.apply_in_place(offset, &mut output);
+    output
+  }
+
+  /// XORs the keystream for `offset..offset + buf.len()` into `buf`, a block at a time, so that
+  /// large buffers never need a keystream of their own - and nothing is allocated per block.
+  pub fn apply_in_place(&self, offset: usize, buf: &mut [u8]) {
+    let bs = self.cipher.block_size();
+    let mut stream = [0u8; 16];
+    let mut pos = 0;
+    while pos < buf.len() {
+      let at = offset + pos;
+      self.cipher.encrypt_block(&self.format.counter_block(&self.nonce, (at / bs) as u64),
+                                &mut stream);
+      let skip = at % bs;
+      let take = ::std::cmp::min(bs - skip, buf.len() - pos);
+      for (b, k) in buf[pos..pos + take].iter_mut().zip(&stream[skip..skip + take]) {
+        *b ^= *k;
+      }
+      pos += take;
+    }
+  }
+
+  /// Re-encrypts `ciphertext` so that it decrypts to `newtext` at `offset`, leaving every other
+  /// byte alone.
+  pub fn edit(&self, ciphertext: &mut [u8], offset: usize, newtext: &[u8]) -> Result<()> {
+    if offset + newtext.len() > ciphertext.len() {
+      return Err(CrackError::Str("edit runs past the end of the ciphertext"));
+    }
+    let region = &mut ciphertext[offset..offset + newtext.len()];
+    region.copy_from_slice(newtext);
+    self.apply_in_place(offset, region);
+    Ok(())
+  }
+
+  pub fn encrypt(&self, input: &[u8]) -> Vec<u8> {
+    self.apply(0, input)
+  }
+
+  pub fn decrypt(&self, input: &[u8]) -> Vec<u8> {
+    self.apply(0, input)
+  }
+}
+
+pub fn encrypt(key: &[u8], nonce: u64, input: &[u8]) -> Result<Vec<u8>> {
+  Ok(try!(Ctr::cryptopals(key, nonce)).encrypt(input))
+}
+
+pub fn decrypt(key: &[u8], nonce: u64, input: &[u8]) -> Result<Vec<u8>> {
+  encrypt(key, nonce, input)
+}
+
+pub fn edit(ciphertext: &mut [u8],
+            key: &[u8],
+            nonce: u64,
+            offset: usize,
+            newtext: &[u8])
+            -> Result<()> {
+  try!(Ctr::cryptopals(key, nonce)).edit(ciphertext, offset, newtext)
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use aes::Aes;
+  use byte_convert::hex2bytes;
+
+  #[test]
+  fn layouts() {
+    let nonce = [0xaa; 16];
+    assert_eq!(LittleEndian64.counter_block(&nonce[0..8], 0x0102),
+               [0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 2, 1, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(BigEndian32.counter_block(&nonce[0..12], 0x1_0000_0102),
+               [0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0, 0, 1, 2]);
+    assert_eq!(Increment128.counter_block(&[0xff; 16], 1), [0; 16]);
+    assert_eq!(&Increment128.counter_block(&nonce, 0x0157)[12..16],
+               &[0xaa, 0xaa, 0xac, 0x01][..]);
+  }
+
+  // NIST SP 800-38A F.5.1 CTR-AES128.Encrypt
+  #[test]
+  fn nist_vector() {
+    let ctr = Ctr::new(Aes::new(&hex2bytes("2b7e151628aed2a6abf7158809cf4f3c").unwrap()).unwrap(),
+                       Increment128,
+                       &hex2bytes("f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff").unwrap())
+      .unwrap();
+    let plain = hex2bytes("6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51")
+      .unwrap();
+    assert_eq!(ctr.encrypt(&plain),
+               hex2bytes("874d6191b620e3261bef6864990db6ce9806f66b7970fdff8617187bb9fffdff")
+                 .unwrap());
+  }
+
+  #[test]
+  fn seekable() {
+    let ctr = Ctr::cryptopals("YELLOW SUBMARINE".as_bytes(), 0).unwrap();
+    let whole = ctr.keystream(0, 100);
+    assert_eq!(whole.len(), 100);
+    assert_eq!(ctr.keystream(37, 40), &whole[37..77]);
+    assert_eq!(ctr.keystream(16, 0), vec![]);
+
+    let message = "Attack the castle gates from the high west wall.".as_bytes();
+    let crypted = ctr.encrypt(message);
+    assert_eq!(&ctr.apply(0, &crypted)[..], message);
+    assert_eq!(&ctr.apply(21, &crypted[21..])[..], &message[21..]);
+  }
+
+  #[test]
+  fn edits_in_place() {
+    let key = "YELLOW SUBMARINE".as_bytes();
+    let message = "Attack the castle gates from the high west wall.".as_bytes();
+    let mut crypted = encrypt(key, 3, message).unwrap();
+    let original = crypted.clone();
+
+    edit(&mut crypted, key, 3, 18, "doors".as_bytes()).unwrap();
+    assert_eq!(&crypted[..18], &original[..18]);
+    assert_eq!(&crypted[23..], &original[23..]);
+    assert_eq!(decrypt(key, 3, &crypted).unwrap(),
+               "Attack the castle doors from the high west wall.".as_bytes());
+
+    assert!(edit(&mut crypted, key, 3, 45, "gates".as_bytes()).is_err());
+  }
+}
@@ -1,29 +1,36 @@
-use crypto::{aes, blockmodes, buffer};
-use std::iter;
 use result::*;
+use super::{Aes, BlockCipher};
 
 pub fn decrypt(key: &[u8], input: &[u8]) -> Result<Vec<u8>> {
-  let mut out: Vec<u8> = iter::repeat(0u8).take(input.len()).collect();
-  let mut decryptor = aes::ecb_decryptor(aes::KeySize::KeySize128, key, blockmodes::NoPadding);
-
-  {
-    let mut cbuf = buffer::RefReadBuffer::new(input);
-    let mut pbuf = buffer::RefWriteBuffer::new(out.as_mut_slice());
-    try!(decryptor.decrypt(&mut cbuf, &mut pbuf, true));
-  }
-  Ok(out.clone())
+  decrypt_with(&try!(Aes::new(key)), input)
 }
 
 pub fn encrypt(key: &[u8], input: &[u8]) -> Result<Vec<u8>> {
-  let mut out: Vec<u8> = iter::repeat(0u8).take(input.len()).collect();
-  let mut encryptor = aes::ecb_encryptor(aes::KeySize::KeySize128, key, blockmodes::NoPadding);
+  encrypt_with(&try!(Aes::new(key)), input)
+}
+
+pub fn decrypt_with<C: BlockCipher + ?Sized>(cipher: &C, input: &[u8]) -> Result<Vec<u8>> {
+  let bs = cipher.block_size();
+  if !input.len().is_multiple_of(bs) {
+    return Err(CrackError::Str("ECB input must be a whole number of blocks"));
+  }
+  let mut out = vec![0u8; input.len()];
+  for (block, out_block) in input.chunks(bs).zip(out.chunks_mut(bs)) {
+    cipher.decrypt_block(block, out_block);
+  }
+  Ok(out)
+}
 
-  {
-    let mut cbuf = buffer::RefReadBuffer::new(input);
-    let mut pbuf = buffer::RefWriteBuffer::new(out.as_mut_slice());
-    try!(encryptor.encrypt(&mut cbuf, &mut pbuf, true));
+pub fn encrypt_with<C: BlockCipher + ?Sized>(cipher: &C, input: &[u8]) -> Result<Vec<u8>> {
+  let bs = cipher.block_size();
+  if !input.len().is_multiple_of(bs) {
+    return Err(CrackError::Str("ECB input must be a whole number of blocks"));
+  }
+  let mut out = vec![0u8; input.len()];
+  for (block, out_block) in input.chunks(bs).zip(out.chunks_mut(bs)) {
+    cipher.encrypt_block(block, out_block);
   }
-  Ok(out.clone())
+  Ok(out)
 }
 
 #[cfg(test)]
@@ -39,4 +46,20 @@ mod test {
     println!("{} {:?}", crypted.len(), crypted);
     assert_eq!(message, decrypt(key, &crypted).unwrap().as_slice())
   }
+
+  #[test]
+  fn longer_keys() {
+    let message = "Attack the castle gates from the high west wall.".as_bytes();
+    let short = encrypt("YELLOW SUBMARINE".as_bytes(), message).unwrap();
+    for key in &["YELLOW SUBMARINE AND RED", "YELLOW SUBMARINE AND RED WAGONS!"] {
+      let crypted = encrypt(key.as_bytes(), message).unwrap();
+      assert!(crypted != short);
+      assert_eq!(message, decrypt(key.as_bytes(), &crypted).unwrap().as_slice())
+    }
+  }
+
+  #[test]
+  fn partial_block() {
+    assert!(encrypt("YELLOW SUBMARINE".as_bytes(), "too short".as_bytes()).is_err())
+  }
 }
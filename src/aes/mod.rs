@@ -1,2 +1,6 @@
 pub mod ecb;
 pub mod cbc;
+pub mod ctr;
+mod cipher;
+
+pub use self::cipher::{BlockCipher, Aes};
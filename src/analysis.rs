@@ -1,22 +1,40 @@
+use oracle::EncryptOracle;
+use result::{Result, CrackError};
+
 #[derive(Debug,PartialEq,Clone,Copy)]
 pub enum Mode {
   ElectronicCodebook,
   CipherBlockChaining,
 }
 
+/// Encrypts `a` and `b` and returns the index of the first `chunk_size` chunk where the
+/// ciphertexts differ - a block for block ciphers, a byte (`chunk_size` 1) for stream ciphers.
+/// With inputs that differ in one place, that's where the oracle put it.
+pub fn first_difference<O: EncryptOracle + ?Sized>(oracle: &O,
+                                                   a: &[u8],
+                                                   b: &[u8],
+                                                   chunk_size: usize)
+                                                   -> Result<usize> {
+  let crypt_a = try!(oracle.encrypt(a));
+  let crypt_b = try!(oracle.encrypt(b));
+  crypt_a.chunks(chunk_size)
+    .zip(crypt_b.chunks(chunk_size))
+    .position(|(l, r)| l != r)
+    .ok_or(CrackError::Str("input doesn't affect the ciphertext"))
+}
+
 pub mod aes {
   use super::Mode;
   use std::iter::repeat;
   use num_bigint::{BigInt, Sign};
   use frequency;
   use result::Result;
+  use oracle::EncryptOracle;
 
-  pub fn detector<F>(blocksize: usize, oracle: F) -> Result<Mode>
-    where F: for<'g> Fn(&'g [u8]) -> Result<Vec<u8>>
-  {
+  pub fn detector<O: EncryptOracle + ?Sized>(blocksize: usize, oracle: &O) -> Result<Mode> {
     let exploit_message: Vec<u8> = repeat(b'a').take(3 * blocksize).collect(); // 6 * 16 = blocksize (should work down to 3)
 
-    let crypt = try!(oracle(&exploit_message));
+    let crypt = try!(oracle.encrypt(&exploit_message));
     let chunks =
       (&crypt.as_slice()).chunks(blocksize).map(|ch| BigInt::from_bytes_be(Sign::Plus, ch));
     let c = frequency::Counts::new(chunks);
@@ -28,3 +46,352 @@ pub mod aes {
     }
   }
 }
+
+pub mod cbc {
+  use xor;
+  use padding;
+  use random;
+  use oracle::PaddingOracle;
+  use result::{Result, CrackError};
+
+  /// Rewrites `ciphertext` so that block `target_block`, which decrypts to `known`, will decrypt
+  /// to `desired` instead.  The block before it takes the XOR delta, so it decrypts to garbage.
+  ///
+  /// If the IV travels separately, prepend it to the ciphertext to reach the first block.
+  pub fn bitflip(ciphertext: &[u8],
+                 block_size: usize,
+                 target_block: usize,
+                 known: &[u8],
+                 desired: &[u8])
+                 -> Result<Vec<u8>> {
+    if target_block == 0 || (target_block + 1) * block_size > ciphertext.len() ||
+       known.len() != block_size || desired.len() != block_size {
+      return Err(CrackError::Str("bitflip needs a whole block with a block before it"));
+    }
+    let delta: Vec<u8> = xor::xor_iters(known, desired);
+    let mut flipped = ciphertext.to_vec();
+    let prev = (target_block - 1) * block_size;
+    for (c, d) in flipped[prev..prev + block_size].iter_mut().zip(delta) {
+      *c ^= d;
+    }
+    Ok(flipped)
+  }
+
+  /// Recovers the whole plaintext (padding included) of `ciphertext`, given the `iv` it was sent
+  /// with and an oracle that says whether a ciphertext's padding is valid.
+  pub fn padding_oracle_decrypt<O: PaddingOracle + ?Sized>(oracle: &O,
+                                                           iv: &[u8],
+                                                           ciphertext: &[u8])
+                                                           -> Result<Vec<u8>> {
+    let bs = iv.len();
+    if bs == 0 || !ciphertext.len().is_multiple_of(bs) {
+      return Err(CrackError::Str("ciphertext isn't whole blocks of the IV's size"));
+    }
+    let mut plain = vec![];
+    let mut prev = iv;
+    for block in ciphertext.chunks(bs) {
+      let intermediate = try!(intermediate_block(oracle, block));
+      plain.extend(xor::xor_iters::<_, _, Vec<u8>, _>(&intermediate, prev));
+      prev = block;
+    }
+    Ok(plain)
+  }
+
+  /// CBC-R: builds an (IV, ciphertext) pair that decrypts to `plaintext` (PKCS#7 padded here)
+  /// under the oracle's key.  Working back from a random last block, each block's intermediate
+  /// decryption is learned from the oracle and the block before it chosen to produce our text.
+  pub fn padding_oracle_encrypt<O: PaddingOracle + ?Sized>(oracle: &O,
+                                                           block_size: usize,
+                                                           plaintext: &[u8])
+                                                           -> Result<(Vec<u8>, Vec<u8>)> {
+    let padded = padding::pkcs7(plaintext, block_size);
+    let mut current = random::bytes(block_size);
+    let mut blocks = vec![];
+    for block in padded.chunks(block_size).rev() {
+      let intermediate = try!(intermediate_block(oracle, &current));
+      let prev: Vec<u8> = xor::xor_iters(&intermediate, block);
+      blocks.push(current);
+      current = prev;
+    }
+    blocks.reverse();
+    Ok((current, blocks.concat()))
+  }
+
+  /// The raw block decryption of `block` - what it decrypts to before the XOR with whatever
+  /// precedes it - worked out a byte at a time from the end by forging the preceding block.
+  pub fn intermediate_block<O: PaddingOracle + ?Sized>(oracle: &O, block: &[u8]) -> Result<Vec<u8>> {
+    let bs = block.len();
+    let mut intermediate = vec![0u8; bs];
+    for pad in 1..bs + 1 {
+      let pos = bs - pad;
+      let mut forged = vec![0u8; bs];
+      for i in pos + 1..bs {
+        forged[i] = intermediate[i] ^ pad as u8;
+      }
+      let mut found = None;
+      for guess in 0..256 {
+        forged[pos] = guess as u8;
+        if try!(oracle.valid_padding(&forged, block)) &&
+           try!(not_longer_padding(oracle, &forged, block, pos)) {
+          found = Some(guess as u8);
+          break;
+        }
+      }
+      match found {
+        Some(guess) => intermediate[pos] = guess ^ pad as u8,
+        None => return Err(CrackError::Str("no forged byte gave valid padding")),
+      }
+    }
+    Ok(intermediate)
+  }
+
+  /// When working on the last byte, a hit might really be `\x02\x02` (or longer) padding that
+  /// the untouched bytes happened to make.  Disturbing the byte before rules that out.
+  fn not_longer_padding<O: PaddingOracle + ?Sized>(oracle: &O,
+                                                   forged: &[u8],
+                                                   block: &[u8],
+                                                   pos: usize)
+                                                   -> Result<bool> {
+    if pos != forged.len() - 1 || pos == 0 {
+      return Ok(true);
+    }
+    let mut disturbed = forged.to_vec();
+    disturbed[pos - 1] ^= 0xff;
+    oracle.valid_padding(&disturbed, block)
+  }
+
+  #[cfg(test)]
+  mod test {
+    use super::*;
+    use result::Result;
+
+    /// "Decrypts" every block to a fixed intermediate, so the plaintext is just that XOR the IV.
+    struct FixedIntermediate(Vec<u8>);
+
+    impl PaddingOracle for FixedIntermediate {
+      fn valid_padding(&self, iv: &[u8], _: &[u8]) -> Result<bool> {
+        let plain: Vec<u8> = xor::xor_iters(&self.0, iv);
+        Ok(padding::unpad_pkcs7(&plain, plain.len()).is_ok())
+      }
+    }
+
+    #[test]
+    fn rejects_longer_padding() {
+      // With a zeroed forged block the second-to-last byte decrypts to 0x02, so forging 0x35
+      // gives valid `\x02\x02` padding before 0x36 gives `\x01`
+      let mut intermediate: Vec<u8> = (0..16).map(|n| 0x40 + n).collect();
+      intermediate[14] = 0x02;
+      intermediate[15] = 0x37;
+      let oracle = FixedIntermediate(intermediate.clone());
+      assert!(oracle.valid_padding(&[&[0u8; 15][..], &[0x35]].concat(), &[0; 16]).unwrap());
+
+      assert_eq!(intermediate_block(&oracle, &[0; 16]).unwrap(), intermediate);
+    }
+  }
+}
+
+pub mod ctr {
+  use xor;
+  use oracle::EncryptOracle;
+  use result::{Result, CrackError};
+
+  /// Rewrites `ciphertext` so that the bytes at `offset`, which decrypt to `known`, will decrypt
+  /// to `desired` instead.  Nothing else changes: a stream cipher has no neighbouring block to
+  /// scramble.
+  pub fn bitflip(ciphertext: &[u8],
+                 offset: usize,
+                 known: &[u8],
+                 desired: &[u8])
+                 -> Result<Vec<u8>> {
+    if known.len() != desired.len() || offset + known.len() > ciphertext.len() {
+      return Err(CrackError::Str("bitflip target doesn't fit in the ciphertext"));
+    }
+    let delta: Vec<u8> = xor::xor_iters(known, desired);
+    let mut flipped = ciphertext.to_vec();
+    for (c, d) in flipped[offset..offset + known.len()].iter_mut().zip(delta) {
+      *c ^= d;
+    }
+    Ok(flipped)
+  }
+
+  /// Finds where attacker input starts in a stream-encrypted message, by encrypting two different
+  /// one-byte inputs and finding the first byte where the ciphertexts disagree.
+  pub fn input_offset<O: EncryptOracle + ?Sized>(oracle: &O) -> Result<usize> {
+    super::first_difference(oracle, b"A", b"B", 1)
+  }
+}
+
+pub mod length_extension {
+  use hash::MerkleDamgard;
+  use oracle::VerifyOracle;
+  use result::{Result, CrackError};
+
+  /// Extends a secret-prefix MAC over `message` to cover `message || glue || extension`, assuming
+  /// the secret was `key_len` bytes.  Returns the new message and its MAC.  `mac` has to be a
+  /// whole digest, `H::digest_size()` bytes, or there's no state to resume from.
+  pub fn extend<H: MerkleDamgard>(message: &[u8],
+                                  mac: &[u8],
+                                  key_len: usize,
+                                  extension: &[u8])
+                                  -> Result<(Vec<u8>, Vec<u8>)> {
+    if mac.len() != H::digest_size() {
+      return Err(CrackError::Str("MAC is not a whole digest"));
+    }
+    let glue = H::padding((key_len + message.len()) as u64);
+    let forged = [message, &glue, extension].concat();
+    let mut hash = H::from_digest(mac, (key_len + message.len() + glue.len()) as u64);
+    hash.update(extension);
+    Ok((forged, hash.digest()))
+  }
+
+  /// Guesses every key length up to `max_key_len` until the oracle accepts a forgery.
+  pub fn forge<H, O>(oracle: &O,
+                     message: &[u8],
+                     mac: &[u8],
+                     extension: &[u8],
+                     max_key_len: usize)
+                     -> Result<(Vec<u8>, Vec<u8>)>
+    where H: MerkleDamgard,
+          O: VerifyOracle + ?Sized
+  {
+    for key_len in 0..max_key_len + 1 {
+      let (forged, forged_mac) = try!(extend::<H>(message, mac, key_len, extension));
+      if try!(oracle.verify(&forged, &forged_mac)) {
+        return Ok((forged, forged_mac));
+      }
+    }
+    Err(CrackError::Str("no key length gave an accepted MAC"))
+  }
+}
+
+pub mod timing {
+  use std::time::{Duration, Instant};
+  use oracle::VerifyOracle;
+  use result::{Result, CrackError};
+
+  fn nanos(duration: Duration) -> u64 {
+    duration.as_secs() * 1_000_000_000 + duration.subsec_nanos() as u64
+  }
+
+  /// Where `recover_mac` reads the time, in nanoseconds from any fixed point.  `Stopwatch` is the
+  /// real thing; a simulated oracle can be its own timer and skip the waiting.
+  pub trait Timer {
+    fn nanos(&self) -> u64;
+  }
+
+  pub struct Stopwatch {
+    start: Instant,
+  }
+
+  impl Stopwatch {
+    pub fn new() -> Stopwatch {
+      Stopwatch { start: Instant::now() }
+    }
+  }
+
+  impl Default for Stopwatch {
+    fn default() -> Stopwatch {
+      Stopwatch::new()
+    }
+  }
+
+  impl Timer for Stopwatch {
+    fn nanos(&self) -> u64 {
+      nanos(self.start.elapsed())
+    }
+  }
+
+  /// Asks the oracle about `mac` `samples` times, returning whether it was accepted and the
+  /// median time taken.  The median shrugs off the odd slow round trip that would drag a mean.
+  fn median_time<O, T>(oracle: &O,
+                       timer: &T,
+                       message: &[u8],
+                       mac: &[u8],
+                       samples: usize)
+                       -> Result<(bool, u64)>
+    where O: VerifyOracle + ?Sized,
+          T: Timer + ?Sized
+  {
+    let mut times = Vec::with_capacity(samples);
+    for _ in 0..samples {
+      let start = timer.nanos();
+      if try!(oracle.verify(message, mac)) {
+        return Ok((true, 0));
+      }
+      times.push(timer.nanos() - start);
+    }
+    times.sort();
+    Ok((false, times[times.len() / 2]))
+  }
+
+  /// Recovers a `mac_len` byte MAC for `message` from an oracle whose comparison bails out at the
+  /// first wrong byte, one byte at a time: the right guess is the one that takes longest to reject.
+  ///
+  /// Every guess is timed `samples` times, and the few slowest are timed again with four times as
+  /// many before one is picked, so a single noisy batch can't derail the rest of the MAC.  The
+  /// last byte needs no timing at all, since the oracle says when the MAC is right.
+  pub fn recover_mac<O: VerifyOracle + ?Sized>(oracle: &O,
+                                               message: &[u8],
+                                               mac_len: usize,
+                                               samples: usize)
+                                               -> Result<Vec<u8>> {
+    recover_mac_with(oracle, &Stopwatch::new(), message, mac_len, samples)
+  }
+
+  /// `recover_mac`, reading the time from `timer`.
+  pub fn recover_mac_with<O, T>(oracle: &O,
+                                timer: &T,
+                                message: &[u8],
+                                mac_len: usize,
+                                samples: usize)
+                                -> Result<Vec<u8>>
+    where O: VerifyOracle + ?Sized,
+          T: Timer + ?Sized
+  {
+    if samples == 0 {
+      return Err(CrackError::Str("need at least one timing sample per guess"));
+    }
+    let mut mac = vec![0u8; mac_len];
+    for pos in 0..mac_len {
+      let samples = if pos + 1 == mac_len { 1 } else { samples };
+      let mut timings = Vec::with_capacity(256);
+      for byte in 0..256 {
+        mac[pos] = byte as u8;
+        let (valid, time) = try!(median_time(oracle, timer, message, &mac, samples));
+        if valid {
+          return Ok(mac);
+        }
+        timings.push((time, byte as u8));
+      }
+      timings.sort_by(|a, b| b.cmp(a));
+
+      let mut best = (0, timings[0].1);
+      for &(_, byte) in timings.iter().take(3) {
+        mac[pos] = byte;
+        let (valid, time) = try!(median_time(oracle, timer, message, &mac, samples * 4));
+        if valid {
+          return Ok(mac);
+        }
+        if time > best.0 {
+          best = (time, byte);
+        }
+      }
+      mac[pos] = best.1;
+    }
+    Err(CrackError::Str("no guess at the MAC was accepted"))
+  }
+}
+
+pub mod mt19937 {
+  use random::Mt19937;
+
+  /// Tries every seed in `seeds` until one gives a generator that `matches` accepts.  The
+  /// predicate gets a freshly seeded generator, so it can check outputs, bytes, or whatever else
+  /// the victim made with it.
+  pub fn seed_search<I, F>(seeds: I, mut matches: F) -> Option<u32>
+    where I: IntoIterator<Item = u32>,
+          F: FnMut(&mut Mt19937) -> bool
+  {
+    seeds.into_iter().find(|&seed| matches(&mut Mt19937::new(seed)))
+  }
+}
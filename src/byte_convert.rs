@@ -41,3 +41,14 @@ pub fn open_base64_path(path: &str) -> Result<Vec<u8>> {
   try!(buf.read_to_end(&mut b64bytes));
   b64bytes.from_base64().map_err(|e| CrackError::from(e))
 }
+
+pub fn open_base64_lines_path(path: &str) -> Result<Vec<Vec<u8>>> {
+  let file = try!(File::open(path));
+  let buf = BufReader::new(file);
+  buf.lines()
+    .map(|line| {
+      let string = try!(line);
+      string.from_base64().map_err(CrackError::from)
+    })
+    .collect()
+}
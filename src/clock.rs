@@ -0,0 +1,55 @@
+use std::cell::Cell;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Wall-clock time in whole seconds, and a way to let it pass.  Simulations take one of these so
+/// that tests can skip the waiting.
+pub trait Clock {
+  fn now(&self) -> u64;
+  fn sleep(&self, secs: u64);
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+  fn now(&self) -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+  }
+
+  fn sleep(&self, secs: u64) {
+    thread::sleep(Duration::from_secs(secs))
+  }
+}
+
+/// A clock that only moves when something sleeps on it.
+pub struct FakeClock {
+  now: Cell<u64>,
+}
+
+impl FakeClock {
+  pub fn new(start: u64) -> FakeClock {
+    FakeClock { now: Cell::new(start) }
+  }
+}
+
+impl Clock for FakeClock {
+  fn now(&self) -> u64 {
+    self.now.get()
+  }
+
+  fn sleep(&self, secs: u64) {
+    self.now.set(self.now.get() + secs)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn fake_clock_advances() {
+    let clock = FakeClock::new(1000);
+    clock.sleep(40);
+    assert_eq!(clock.now(), 1040);
+  }
+}
@@ -0,0 +1,146 @@
+use num::{One, Zero};
+use num_bigint::{BigUint, RandBigInt};
+use rand::Rng;
+use hash::sha1;
+
+/// `base^exponent mod modulus`, by square-and-multiply over the exponent's bits.
+pub fn modexp(base: &BigUint, exponent: &BigUint, modulus: &BigUint) -> BigUint {
+  let mut result = BigUint::one() % modulus;
+  let mut square = base % modulus;
+  for byte in exponent.to_bytes_le() {
+    for bit in 0..8 {
+      if byte >> bit & 1 == 1 {
+        result = result * &square % modulus;
+      }
+      square = &square * &square % modulus;
+    }
+  }
+  result
+}
+
+/// A prime modulus and a generator: everything both sides agree on before they start.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Group {
+  pub p: BigUint,
+  pub g: BigUint,
+}
+
+lazy_static! {
+  // RFC 3526 1536-bit MODP group, the "NIST" prime from the challenge
+  static ref NIST_P: BigUint = BigUint::parse_bytes(concat!(
+    "ffffffffffffffffc90fdaa22168c234c4c6628b80dc1cd129024e088a67cc74020bbea63b139b22514a0879",
+    "8e3404ddef9519b3cd3a431b302b0a6df25f14374fe1356d6d51c245e485b576625e7ec6f44c42e9a637ed6b",
+    "0bff5cb6f406b7edee386bfb5a899fa5ae9f24117c4b1fe649286651ece45b3dc2007cb8a163bf0598da4836",
+    "1c55d39a69163fa8fd24cf5f83655d23dca3ad961c62f356208552bb9ed529077096966d670c354e4abc9804",
+    "f1746c08ca237327ffffffffffffffff").as_bytes(), 16).unwrap();
+}
+
+impl Group {
+  pub fn new(p: BigUint, g: BigUint) -> Group {
+    Group { p: p, g: g }
+  }
+
+  /// The 1536 bit MODP group with generator 2.
+  pub fn nist() -> Group {
+    Group::new(NIST_P.clone(), BigUint::from(2u32))
+  }
+
+  /// A private key below `p` drawn from `rng`, and its public key.  Seed the generator to get the
+  /// same keys again.
+  pub fn keypair<R: Rng>(&self, rng: &mut R) -> Keypair {
+    let private = rng.gen_biguint_below(&self.p);
+    self.keypair_from(private)
+  }
+
+  pub fn keypair_from(&self, private: BigUint) -> Keypair {
+    Keypair {
+      public: modexp(&self.g, &private, &self.p),
+      private: private,
+      group: self.clone(),
+    }
+  }
+}
+
+pub struct Keypair {
+  group: Group,
+  private: BigUint,
+  public: BigUint,
+}
+
+impl Keypair {
+  pub fn public(&self) -> &BigUint {
+    &self.public
+  }
+
+  pub fn group(&self) -> &Group {
+    &self.group
+  }
+
+  /// The secret both sides end up with: the other side's public key to our private power.
+  pub fn shared_secret(&self, other_public: &BigUint) -> BigUint {
+    modexp(other_public, &self.private, &self.group.p)
+  }
+}
+
+/// An AES-128 key from a shared secret: the first 16 bytes of SHA-1 over its big-endian bytes.
+pub fn derive_key(secret: &BigUint) -> Vec<u8> {
+  let bytes = if secret.is_zero() { vec![0] } else { secret.to_bytes_be() };
+  sha1::sha1(&bytes)[..16].to_vec()
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use num_bigint::{BigUint, RandBigInt};
+  use rand::{self, SeedableRng};
+  use random::Mt19937;
+
+  fn small(n: u32) -> BigUint {
+    BigUint::from(n)
+  }
+
+  #[test]
+  fn modexp_matches() {
+    assert_eq!(modexp(&small(4), &small(13), &small(497)), small(445));
+    assert_eq!(modexp(&small(5), &small(0), &small(37)), small(1));
+    assert_eq!(modexp(&small(5), &small(0), &small(1)), small(0));
+
+    let mut rng = rand::thread_rng();
+    let p = Group::nist().p;
+    for _ in 0..4 {
+      let (base, exponent) = (rng.gen_biguint_below(&p), rng.gen_biguint(1024));
+      assert_eq!(modexp(&base, &exponent, &p), base.modpow(&exponent, &p));
+    }
+  }
+
+  #[test]
+  fn tiny_group_agrees() {
+    let group = Group::new(small(37), small(5));
+    let mut rng = rand::thread_rng();
+    for _ in 0..20 {
+      let (a, b) = (group.keypair(&mut rng), group.keypair(&mut rng));
+      assert_eq!(a.shared_secret(b.public()), b.shared_secret(a.public()));
+      assert!(a.public() < &group.p);
+    }
+  }
+
+  #[test]
+  fn nist_group_agrees() {
+    let group = Group::nist();
+    assert_eq!(group.p.bits(), 1536);
+    let mut rng = rand::thread_rng();
+    let (a, b) = (group.keypair(&mut rng), group.keypair(&mut rng));
+    let secret = a.shared_secret(b.public());
+    assert_eq!(secret, b.shared_secret(a.public()));
+    assert_eq!(derive_key(&secret).len(), 16);
+  }
+
+  #[test]
+  fn seeded_keypairs_repeat() {
+    let group = Group::nist();
+    let a = group.keypair(&mut Mt19937::from_seed(33));
+    let b = group.keypair(&mut Mt19937::from_seed(33));
+    assert_eq!(a.public(), b.public());
+    assert!(a.public() != group.keypair(&mut Mt19937::from_seed(34)).public());
+  }
+}
@@ -0,0 +1,128 @@
+use super::{MerkleDamgard, Blocks, Endian, bytes_to_words, words_to_bytes};
+
+const INITIAL_STATE: [u32; 4] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476];
+
+/// MD4 (RFC 1320).  Like SHA-1 but little-endian throughout, length included.
+#[derive(Clone)]
+pub struct Md4 {
+  state: [u32; 4],
+  blocks: Blocks,
+}
+
+impl Md4 {
+  /// Resumes hashing with `state` after `length` bytes, which should be a whole number of blocks.
+  pub fn from_state(state: [u32; 4], length: u64) -> Md4 {
+    Md4 {
+      state: state,
+      blocks: Blocks::new(length),
+    }
+  }
+
+  /// Splits a digest back into the state words it was written from.
+  pub fn state_from_digest(digest: &[u8]) -> [u32; 4] {
+    let mut state = [0u32; 4];
+    state.copy_from_slice(&bytes_to_words(&digest[..16], Endian::Little));
+    state
+  }
+}
+
+impl MerkleDamgard for Md4 {
+  fn new() -> Md4 {
+    Md4::from_state(INITIAL_STATE, 0)
+  }
+
+  fn from_digest(digest: &[u8], length: u64) -> Md4 {
+    Md4::from_state(Md4::state_from_digest(digest), length)
+  }
+
+  fn digest_size() -> usize {
+    16
+  }
+
+  fn endian() -> Endian {
+    Endian::Little
+  }
+
+  fn update(&mut self, data: &[u8]) {
+    let state = &mut self.state;
+    self.blocks.input(data, |block| compress(state, block));
+  }
+
+  fn digest(mut self) -> Vec<u8> {
+    let pad = Md4::padding(self.blocks.length());
+    self.update(&pad);
+    words_to_bytes(&self.state, Endian::Little)
+  }
+}
+
+// Message word order and rotations for each of the three rounds
+const ORDER: [[usize; 16]; 3] = [[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+                                 [0, 4, 8, 12, 1, 5, 9, 13, 2, 6, 10, 14, 3, 7, 11, 15],
+                                 [0, 8, 4, 12, 2, 10, 6, 14, 1, 9, 5, 13, 3, 11, 7, 15]];
+const SHIFTS: [[u32; 4]; 3] = [[3, 7, 11, 19], [3, 5, 9, 13], [3, 9, 11, 15]];
+const CONSTANTS: [u32; 3] = [0, 0x5a827999, 0x6ed9eba1];
+
+fn compress(state: &mut [u32; 4], block: &[u8]) {
+  let x = bytes_to_words(block, Endian::Little);
+  let mut v = *state;
+  for round in 0..3 {
+    for step in 0..16 {
+      // a, d, c, b take turns as the word being updated
+      let t = (4 - step % 4) % 4;
+      let (b, c, d) = (v[(t + 1) % 4], v[(t + 2) % 4], v[(t + 3) % 4]);
+      let f = match round {
+        0 => (b & c) | (!b & d),
+        1 => (b & c) | (b & d) | (c & d),
+        _ => b ^ c ^ d,
+      };
+      v[t] = v[t]
+        .wrapping_add(f)
+        .wrapping_add(x[ORDER[round][step]])
+        .wrapping_add(CONSTANTS[round])
+        .rotate_left(SHIFTS[round][step % 4]);
+    }
+  }
+  for (s, v) in state.iter_mut().zip(&v) {
+    *s = s.wrapping_add(*v);
+  }
+}
+
+pub fn md4(data: &[u8]) -> Vec<u8> {
+  let mut hash = Md4::new();
+  hash.update(data);
+  hash.digest()
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use hash::MerkleDamgard;
+  use serialize::hex::ToHex;
+
+  #[test]
+  fn rfc1320_vectors() {
+    assert_eq!(md4(b"").to_hex(), "31d6cfe0d16ae931b73c59d7e0c089c0");
+    assert_eq!(md4(b"a").to_hex(), "bde52cb31de33e46245e05fbdbd6fb24");
+    assert_eq!(md4(b"abc").to_hex(), "a448017aaf21d8525fc10ae87aa6729d");
+    assert_eq!(md4(b"message digest").to_hex(), "d9130a8164549fe818874806e1c7014b");
+    assert_eq!(md4(b"abcdefghijklmnopqrstuvwxyz").to_hex(),
+               "d79e1c308aa5bbcdeea8ed63df412da9");
+    assert_eq!(md4(&b"1234567890".repeat(8)).to_hex(), "e33b4ddc9c38f2199c3e7b164fcc0536");
+  }
+
+  #[test]
+  fn little_endian_length() {
+    let pad = Md4::padding(3);
+    assert_eq!(pad.len(), 61);
+    assert_eq!(&pad[53..], &[24, 0, 0, 0, 0, 0, 0, 0][..]);
+  }
+
+  #[test]
+  fn resumes_from_digest() {
+    let head = b"The quick brown fox jumps over the lazy dog";
+    let glued = [&head[..], &Md4::padding(head.len() as u64)].concat();
+    let mut resumed = Md4::from_digest(&md4(head), glued.len() as u64);
+    resumed.update(b";admin=true");
+    assert_eq!(resumed.digest(), md4(&[&glued[..], b";admin=true"].concat()));
+  }
+}
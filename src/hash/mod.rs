@@ -0,0 +1,155 @@
+use std::mem;
+use crypto::digest::Digest;
+
+pub mod sha1;
+pub mod md4;
+
+pub use self::sha1::Sha1;
+pub use self::md4::Md4;
+
+/// Anything that hashes a stream of bytes, as far as constructions like HMAC care.
+///
+/// `finalize` hands back the digest and leaves the hash ready for a new message.
+pub trait Hash {
+  fn block_size(&self) -> usize;
+  fn output_size(&self) -> usize;
+  fn update(&mut self, data: &[u8]);
+  fn finalize(&mut self) -> Vec<u8>;
+}
+
+/// Adapts one of rust-crypto's digests, e.g. `Digested(crypto::sha2::Sha256::new())`.
+pub struct Digested<D>(pub D);
+
+impl<D: Digest> Hash for Digested<D> {
+  fn block_size(&self) -> usize {
+    self.0.block_size()
+  }
+
+  fn output_size(&self) -> usize {
+    self.0.output_bytes()
+  }
+
+  fn update(&mut self, data: &[u8]) {
+    self.0.input(data)
+  }
+
+  fn finalize(&mut self) -> Vec<u8> {
+    let mut out = vec![0u8; self.0.output_bytes()];
+    self.0.result(&mut out);
+    self.0.reset();
+    out
+  }
+}
+
+/// How a hash writes its words and the trailing message length.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Endian {
+  Big,
+  Little,
+}
+
+/// A Merkle-Damgård hash: a compression function run over 64 byte blocks, with the message
+/// length written into the final padding.
+///
+/// The digest is the chaining state after the last block, so every such hash can pick up where a
+/// published digest left off - which is all a length extension attack needs.
+pub trait MerkleDamgard: Sized {
+  fn new() -> Self;
+  /// Carries on from `digest` as though `length` bytes (a whole number of blocks, padding
+  /// included) had already been hashed.
+  fn from_digest(digest: &[u8], length: u64) -> Self;
+  /// How many bytes `digest` returns, and `from_digest` expects.
+  fn digest_size() -> usize;
+  fn endian() -> Endian;
+  fn update(&mut self, data: &[u8]);
+  fn digest(self) -> Vec<u8>;
+
+  /// The padding appended to a message of `length` bytes.
+  fn padding(length: u64) -> Vec<u8> {
+    md_padding(length, Self::endian())
+  }
+}
+
+impl<H: MerkleDamgard> Hash for H {
+  fn block_size(&self) -> usize {
+    BLOCK_SIZE
+  }
+
+  fn output_size(&self) -> usize {
+    H::digest_size()
+  }
+
+  fn update(&mut self, data: &[u8]) {
+    MerkleDamgard::update(self, data)
+  }
+
+  fn finalize(&mut self) -> Vec<u8> {
+    mem::replace(self, H::new()).digest()
+  }
+}
+
+pub const BLOCK_SIZE: usize = 64;
+
+/// 0x80, zeros, then the message length in bits as a u64.
+pub fn md_padding(length: u64, endian: Endian) -> Vec<u8> {
+  let zeros = (BLOCK_SIZE * 2 - 9 - length as usize % BLOCK_SIZE) % BLOCK_SIZE;
+  let bits = length.wrapping_mul(8);
+  let words = match endian {
+    Endian::Big => [(bits >> 32) as u32, bits as u32],
+    Endian::Little => [bits as u32, (bits >> 32) as u32],
+  };
+  let mut pad = vec![0x80];
+  pad.extend(vec![0; zeros]);
+  pad.extend(words_to_bytes(&words, endian));
+  pad
+}
+
+/// Buffers input until there's a whole block for the compression function.
+#[derive(Clone)]
+pub struct Blocks {
+  length: u64,
+  buffer: Vec<u8>,
+}
+
+impl Blocks {
+  pub fn new(length: u64) -> Blocks {
+    Blocks {
+      length: length,
+      buffer: Vec::with_capacity(BLOCK_SIZE),
+    }
+  }
+
+  pub fn length(&self) -> u64 {
+    self.length
+  }
+
+  pub fn input<F: FnMut(&[u8])>(&mut self, data: &[u8], mut compress: F) {
+    self.length += data.len() as u64;
+    self.buffer.extend_from_slice(data);
+    let whole = self.buffer.len() - self.buffer.len() % BLOCK_SIZE;
+    for block in self.buffer[..whole].chunks(BLOCK_SIZE) {
+      compress(block);
+    }
+    self.buffer.drain(..whole);
+  }
+}
+
+pub fn bytes_to_words(bytes: &[u8], endian: Endian) -> Vec<u32> {
+  bytes.chunks(4)
+    .map(|b| match endian {
+      Endian::Big => b.iter().fold(0, |w, &b| w << 8 | b as u32),
+      Endian::Little => b.iter().rev().fold(0, |w, &b| w << 8 | b as u32),
+    })
+    .collect()
+}
+
+pub fn words_to_bytes(words: &[u32], endian: Endian) -> Vec<u8> {
+  words.iter()
+    .flat_map(|&w| {
+      (0..4).map(move |n| match endian {
+        Endian::Big => (w >> (24 - 8 * n)) as u8,
+        Endian::Little => (w >> (8 * n)) as u8,
+      })
+    })
+    .collect()
+}
@@ -0,0 +1,122 @@
+use super::{MerkleDamgard, Blocks, Endian, bytes_to_words, words_to_bytes};
+
+const INITIAL_STATE: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+
+/// SHA-1 (FIPS 180-4), with its chaining state out in the open.
+#[derive(Clone)]
+pub struct Sha1 {
+  state: [u32; 5],
+  blocks: Blocks,
+}
+
+impl Sha1 {
+  /// Resumes hashing with `state` after `length` bytes, which should be a whole number of blocks.
+  pub fn from_state(state: [u32; 5], length: u64) -> Sha1 {
+    Sha1 {
+      state: state,
+      blocks: Blocks::new(length),
+    }
+  }
+
+  /// Splits a digest back into the state words it was written from.
+  pub fn state_from_digest(digest: &[u8]) -> [u32; 5] {
+    let mut state = [0u32; 5];
+    state.copy_from_slice(&bytes_to_words(&digest[..20], Endian::Big));
+    state
+  }
+}
+
+impl MerkleDamgard for Sha1 {
+  fn new() -> Sha1 {
+    Sha1::from_state(INITIAL_STATE, 0)
+  }
+
+  fn from_digest(digest: &[u8], length: u64) -> Sha1 {
+    Sha1::from_state(Sha1::state_from_digest(digest), length)
+  }
+
+  fn digest_size() -> usize {
+    20
+  }
+
+  fn endian() -> Endian {
+    Endian::Big
+  }
+
+  fn update(&mut self, data: &[u8]) {
+    let state = &mut self.state;
+    self.blocks.input(data, |block| compress(state, block));
+  }
+
+  fn digest(mut self) -> Vec<u8> {
+    let pad = Sha1::padding(self.blocks.length());
+    self.update(&pad);
+    words_to_bytes(&self.state, Endian::Big)
+  }
+}
+
+fn compress(state: &mut [u32; 5], block: &[u8]) {
+  let mut w = [0u32; 80];
+  w[..16].copy_from_slice(&bytes_to_words(block, Endian::Big));
+  for i in 16..80 {
+    w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+  }
+
+  let (mut a, mut b, mut c, mut d, mut e) = (state[0], state[1], state[2], state[3], state[4]);
+  for (i, &word) in w.iter().enumerate() {
+    let (f, k) = match i {
+      0..=19 => ((b & c) | (!b & d), 0x5a827999),
+      20..=39 => (b ^ c ^ d, 0x6ed9eba1),
+      40..=59 => ((b & c) | (b & d) | (c & d), 0x8f1bbcdc),
+      _ => (b ^ c ^ d, 0xca62c1d6),
+    };
+    let t = a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(word);
+    e = d;
+    d = c;
+    c = b.rotate_left(30);
+    b = a;
+    a = t;
+  }
+  for (s, v) in state.iter_mut().zip(&[a, b, c, d, e]) {
+    *s = s.wrapping_add(*v);
+  }
+}
+
+pub fn sha1(data: &[u8]) -> Vec<u8> {
+  let mut hash = Sha1::new();
+  hash.update(data);
+  hash.digest()
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use hash::MerkleDamgard;
+  use serialize::hex::ToHex;
+
+  #[test]
+  fn fips_vectors() {
+    assert_eq!(sha1(b"abc").to_hex(), "a9993e364706816aba3e25717850c26c9cd0d89d");
+    assert_eq!(sha1(b"").to_hex(), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+    assert_eq!(sha1(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq").to_hex(),
+               "84983e441c3bd26ebaae4aa1f95129e5e54670f1");
+  }
+
+  #[test]
+  fn incremental() {
+    let mut hash = Sha1::new();
+    for _ in 0..10000 {
+      hash.update(&[b'a'; 100]);
+    }
+    assert_eq!(hash.digest().to_hex(), "34aa973cd4c4daa4f61eeb2bdbad27316534016f");
+  }
+
+  #[test]
+  fn resumes_from_digest() {
+    let head = b"The quick brown fox jumps over the lazy dog";
+    let glued = [&head[..], &Sha1::padding(head.len() as u64)].concat();
+    let mut resumed = Sha1::from_state(Sha1::state_from_digest(&sha1(head)), glued.len() as u64);
+    resumed.update(b";admin=true");
+    assert_eq!(resumed.digest(), sha1(&[&glued[..], b";admin=true"].concat()));
+  }
+}
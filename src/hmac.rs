@@ -0,0 +1,124 @@
+use hash::Hash;
+
+const IPAD: u8 = 0x36;
+const OPAD: u8 = 0x5c;
+
+/// HMAC (RFC 2104) over any `Hash`.
+///
+/// Unlike `H(key || message)`, the outer hash runs over a fixed-length input, so there is no
+/// state left lying around for a length extension to resume from.
+pub struct Hmac<H> {
+  hash: H,
+  outer_key: Vec<u8>,
+}
+
+impl<H: Hash> Hmac<H> {
+  pub fn new(mut hash: H, key: &[u8]) -> Hmac<H> {
+    let block_size = hash.block_size();
+    let mut key = if key.len() > block_size {
+      hash.update(key);
+      hash.finalize()
+    } else {
+      key.to_vec()
+    };
+    key.resize(block_size, 0);
+
+    let inner_key: Vec<u8> = key.iter().map(|b| b ^ IPAD).collect();
+    hash.update(&inner_key);
+    Hmac {
+      hash: hash,
+      outer_key: key.iter().map(|b| b ^ OPAD).collect(),
+    }
+  }
+
+  pub fn update(&mut self, data: &[u8]) {
+    self.hash.update(data)
+  }
+
+  pub fn finalize(mut self) -> Vec<u8> {
+    let inner = self.hash.finalize();
+    self.hash.update(&self.outer_key);
+    self.hash.update(&inner);
+    self.hash.finalize()
+  }
+}
+
+/// # Examples
+/// ```
+/// extern crate crypto;
+/// extern crate cryptopals;
+///
+/// use crypto::sha1::Sha1;
+/// use cryptopals::hash::Digested;
+/// use cryptopals::hmac::hmac;
+///
+/// # fn main() {
+/// let mac = hmac(Digested(Sha1::new()), b"Jefe", b"what do ya want for nothing?");
+/// assert_eq!(mac[..4], [0xef, 0xfc, 0xdf, 0x6a]);
+/// # }
+/// ```
+pub fn hmac<H: Hash>(hash: H, key: &[u8], message: &[u8]) -> Vec<u8> {
+  let mut mac = Hmac::new(hash, key);
+  mac.update(message);
+  mac.finalize()
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crypto::sha1::Sha1 as CryptoSha1;
+  use crypto::sha2::Sha256;
+  use hash::{Digested, Hash, MerkleDamgard, Md4, Sha1};
+  use serialize::hex::ToHex;
+
+  fn hmac_sha1(key: &[u8], message: &[u8]) -> String {
+    hmac(Digested(CryptoSha1::new()), key, message).to_hex()
+  }
+
+  fn hmac_sha256(key: &[u8], message: &[u8]) -> String {
+    hmac(Digested(Sha256::new()), key, message).to_hex()
+  }
+
+  #[test]
+  fn rfc2202_sha1() {
+    assert_eq!(hmac_sha1(&[0x0b; 20], b"Hi There"),
+               "b617318655057264e28bc0b6fb378c8ef146be00");
+    assert_eq!(hmac_sha1(b"Jefe", b"what do ya want for nothing?"),
+               "effcdf6ae5eb2fa2d27416d5f184df9c259a7c79");
+    assert_eq!(hmac_sha1(&[0xaa; 20], &[0xdd; 50]),
+               "125d7342b9ac11cd91a39af48aa17b4f63f175d3");
+    assert_eq!(hmac_sha1(&[0xaa; 80],
+                         b"Test Using Larger Than Block-Size Key - Hash Key First"),
+               "aa4ae5e15272d00e95705637ce8a3b55ed402112");
+  }
+
+  #[test]
+  fn rfc4231_sha256() {
+    assert_eq!(hmac_sha256(&[0x0b; 20], b"Hi There"),
+               "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7");
+    assert_eq!(hmac_sha256(b"Jefe", b"what do ya want for nothing?"),
+               "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843");
+    assert_eq!(hmac_sha256(&[0xaa; 131],
+                           b"Test Using Larger Than Block-Size Key - Hash Key First"),
+               "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54");
+  }
+
+  #[test]
+  fn own_hashes() {
+    let key = [0xaa; 80];
+    let message = b"Test Using Larger Than Block-Size Key - Hash Key First";
+    assert_eq!(hmac(Sha1::new(), &key, message).to_hex(), hmac_sha1(&key, message));
+    assert_eq!(hmac(Md4::new(), b"Jefe", b"what do ya want for nothing?").len(),
+               Md4::new().output_size());
+    assert_eq!(Sha1::new().output_size(), 20);
+    assert_eq!(Digested(Sha256::new()).output_size(), 32);
+  }
+
+  #[test]
+  fn streams() {
+    let mut mac = Hmac::new(Sha1::new(), b"Jefe");
+    mac.update(b"what do ya ");
+    mac.update(b"want for nothing?");
+    assert_eq!(mac.finalize().to_hex(), "effcdf6ae5eb2fa2d27416d5f184df9c259a7c79");
+  }
+}
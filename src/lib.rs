@@ -9,12 +9,20 @@ extern crate lazy_static;
 
 pub mod set1;
 pub mod set2;
+pub mod set3;
+pub mod set4;
+pub mod set5;
 pub mod byte_convert;
 mod xor;
 mod frequency;
-mod random;
+pub mod random;
+pub mod clock;
 mod utils;
-mod aes;
-mod padding;
+pub mod aes;
+pub mod padding;
+pub mod hash;
+pub mod hmac;
+pub mod dh;
 pub mod analysis;
+pub mod oracle;
 pub mod result;
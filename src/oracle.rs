@@ -0,0 +1,79 @@
+use result::Result;
+
+/// Something that knows a secret and answers questions about it.
+///
+/// The attacks here only ever talk to the sub-traits below, so an oracle can be an in-process
+/// struct, a closure wrapped in `EncryptFn` or `DecryptFn`, or a client for some remote service.
+/// There's nothing to implement here: everything is an `Oracle`.
+pub trait Oracle {}
+
+/// Encrypts attacker-chosen input, probably wrapped in things the attacker doesn't know.
+pub trait EncryptOracle: Oracle {
+  fn encrypt(&self, input: &[u8]) -> Result<Vec<u8>>;
+}
+
+/// Decrypts attacker-chosen ciphertext.
+pub trait DecryptOracle: Oracle {
+  fn decrypt(&self, input: &[u8]) -> Result<Vec<u8>>;
+}
+
+/// Decrypts a ciphertext but only reports whether its padding was valid.
+pub trait PaddingOracle: Oracle {
+  fn valid_padding(&self, iv: &[u8], ciphertext: &[u8]) -> Result<bool>;
+}
+
+/// Rewrites part of a ciphertext to hold new plaintext, without handing out the key.
+pub trait EditOracle: Oracle {
+  fn edit(&self, ciphertext: &[u8], offset: usize, newtext: &[u8]) -> Result<Vec<u8>>;
+}
+
+/// Checks a MAC over a message, under a key the attacker never sees.
+pub trait VerifyOracle: Oracle {
+  fn verify(&self, message: &[u8], mac: &[u8]) -> Result<bool>;
+}
+
+impl<T: ?Sized> Oracle for T {}
+
+/// A closure standing in as an `EncryptOracle`.  The wrapper says which way the closure goes, so
+/// it can't quietly be passed where a decryptor is wanted too.
+pub struct EncryptFn<F>(pub F);
+
+/// A closure standing in as a `DecryptOracle`.
+pub struct DecryptFn<F>(pub F);
+
+impl<F> EncryptOracle for EncryptFn<F>
+  where F: Fn(&[u8]) -> Result<Vec<u8>>
+{
+  fn encrypt(&self, input: &[u8]) -> Result<Vec<u8>> {
+    (self.0)(input)
+  }
+}
+
+impl<F> DecryptOracle for DecryptFn<F>
+  where F: Fn(&[u8]) -> Result<Vec<u8>>
+{
+  fn decrypt(&self, input: &[u8]) -> Result<Vec<u8>> {
+    (self.0)(input)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use analysis::Mode;
+  use analysis::aes::detector;
+  use result::Result;
+
+  #[test]
+  fn closures_are_oracles() {
+    let identity = |input: &[u8]| -> Result<Vec<u8>> { Ok(input.to_vec()) };
+    assert_eq!(EncryptFn(&identity).encrypt(b"abc").unwrap(), b"abc".to_vec());
+    assert_eq!(DecryptFn(&identity).decrypt(b"abc").unwrap(), b"abc".to_vec());
+    assert_eq!(detector(16, &EncryptFn(identity)).unwrap(), Mode::ElectronicCodebook);
+
+    let counter = EncryptFn(|input: &[u8]| -> Result<Vec<u8>> {
+      Ok(input.iter().enumerate().map(|(i, b)| b ^ i as u8).collect())
+    });
+    assert_eq!(detector(16, &counter).unwrap(), Mode::CipherBlockChaining);
+  }
+}
@@ -1,19 +1,241 @@
-use std::iter::repeat;
+use std::iter::repeat_n;
+use result::{Result, CrackError, PaddingError};
+use random;
+
+/// A block padding scheme. `unpad` is strict: anything that `pad` couldn't have produced is
+/// rejected with a `CrackError::Padding` saying what was wrong with it.
+pub trait Padding {
+  fn pad(&self, data: &[u8], block_size: usize) -> Vec<u8>;
+  fn unpad(&self, data: &[u8], block_size: usize) -> Result<Vec<u8>>;
+}
+
+/// Every pad byte is the pad length.
+pub struct Pkcs7;
+
+/// Zero bytes, with the pad length as the last byte.
+pub struct AnsiX923;
+
+/// A 0x80 marker followed by zero bytes.
+pub struct Iso7816;
+
+/// Random bytes, with the pad length as the last byte.
+pub struct Iso10126;
+
+/// Zero bytes, and none at all if the data already fills its last block.  Data that ends in
+/// zeros can't be recovered.
+pub struct ZeroPadding;
+
 pub fn pkcs7(data: &[u8], block_size: usize) -> Vec<u8> {
-  let padding = block_size - (data.len() % block_size);
-  data.iter()
-    .chain(repeat(&(padding as u8)).take(padding))
-    .map(|c| c.clone())
-    .collect()
+  Pkcs7.pad(data, block_size)
+}
+
+pub fn unpad_pkcs7(data: &[u8], block_size: usize) -> Result<Vec<u8>> {
+  Pkcs7.unpad(data, block_size)
+}
+
+fn pad_len(data: &[u8], block_size: usize) -> usize {
+  block_size - (data.len() % block_size)
+}
+
+fn padded_with<I: IntoIterator<Item = u8>>(data: &[u8], pad: I) -> Vec<u8> {
+  data.iter().cloned().chain(pad).collect()
+}
+
+fn check_aligned(data: &[u8], block_size: usize) -> Result<()> {
+  if data.is_empty() || !data.len().is_multiple_of(block_size) {
+    Err(CrackError::Padding(PaddingError::NotBlockAligned(data.len())))
+  } else {
+    Ok(())
+  }
+}
+
+/// Validates the trailing length byte shared by PKCS#7, X.923 and ISO 10126, returning where the
+/// padding starts.
+fn length_byte(data: &[u8], block_size: usize) -> Result<usize> {
+  try!(check_aligned(data, block_size));
+  let last = data[data.len() - 1];
+  let len = last as usize;
+  if len == 0 || len > block_size {
+    return Err(CrackError::Padding(PaddingError::BadLength(last)));
+  }
+  Ok(data.len() - len)
+}
+
+fn check_fill<F: Fn(u8) -> bool>(data: &[u8], range: ::std::ops::Range<usize>, ok: F) -> Result<()> {
+  for position in range {
+    if !ok(data[position]) {
+      return Err(CrackError::Padding(PaddingError::BadFill {
+        position: position,
+        byte: data[position],
+      }));
+    }
+  }
+  Ok(())
+}
+
+impl Padding for Pkcs7 {
+  fn pad(&self, data: &[u8], block_size: usize) -> Vec<u8> {
+    let padding = pad_len(data, block_size);
+    padded_with(data, repeat_n(padding as u8, padding))
+  }
+
+  fn unpad(&self, data: &[u8], block_size: usize) -> Result<Vec<u8>> {
+    let start = try!(length_byte(data, block_size));
+    let last = data[data.len() - 1];
+    try!(check_fill(data, start..data.len(), |b| b == last));
+    Ok(data[..start].to_vec())
+  }
+}
+
+impl Padding for AnsiX923 {
+  fn pad(&self, data: &[u8], block_size: usize) -> Vec<u8> {
+    let padding = pad_len(data, block_size);
+    padded_with(data, repeat_n(0, padding - 1).chain(Some(padding as u8)))
+  }
+
+  fn unpad(&self, data: &[u8], block_size: usize) -> Result<Vec<u8>> {
+    let start = try!(length_byte(data, block_size));
+    try!(check_fill(data, start..data.len() - 1, |b| b == 0));
+    Ok(data[..start].to_vec())
+  }
+}
+
+impl Padding for Iso7816 {
+  fn pad(&self, data: &[u8], block_size: usize) -> Vec<u8> {
+    let padding = pad_len(data, block_size);
+    padded_with(data, Some(0x80).into_iter().chain(repeat_n(0, padding - 1)))
+  }
+
+  fn unpad(&self, data: &[u8], block_size: usize) -> Result<Vec<u8>> {
+    try!(check_aligned(data, block_size));
+    let last_block = data.len() - block_size;
+    match data[last_block..].iter().rposition(|&b| b != 0) {
+      Some(marker) if data[last_block + marker] == 0x80 => Ok(data[..last_block + marker].to_vec()),
+      _ => Err(CrackError::Padding(PaddingError::NoMarker)),
+    }
+  }
+}
+
+impl Padding for Iso10126 {
+  fn pad(&self, data: &[u8], block_size: usize) -> Vec<u8> {
+    let padding = pad_len(data, block_size);
+    padded_with(data,
+                random::bytes(padding - 1).into_iter().chain(Some(padding as u8)))
+  }
+
+  fn unpad(&self, data: &[u8], block_size: usize) -> Result<Vec<u8>> {
+    let start = try!(length_byte(data, block_size));
+    Ok(data[..start].to_vec())
+  }
+}
+
+impl Padding for ZeroPadding {
+  fn pad(&self, data: &[u8], block_size: usize) -> Vec<u8> {
+    let padding = pad_len(data, block_size) % block_size;
+    padded_with(data, repeat_n(0, padding))
+  }
+
+  fn unpad(&self, data: &[u8], block_size: usize) -> Result<Vec<u8>> {
+    if data.is_empty() {
+      return Ok(vec![]);
+    }
+    try!(check_aligned(data, block_size));
+    let last_block = data.len() - block_size;
+    let end = data[last_block..]
+      .iter()
+      .rposition(|&b| b != 0)
+      .map(|p| last_block + p + 1)
+      .unwrap_or(last_block);
+    Ok(data[..end].to_vec())
+  }
 }
 
 #[cfg(test)]
 mod test {
-  use super::pkcs7;
+  use super::*;
+  use result::{CrackError, PaddingError};
+
+  fn invalid(res: ::result::Result<Vec<u8>>) -> PaddingError {
+    match res {
+      Err(CrackError::Padding(e)) => e,
+      other => panic!("expected a padding error, got {:?}", other),
+    }
+  }
 
   #[test]
   fn padding() {
     assert_eq!(pkcs7("YELLOW SUBMARINE".as_bytes(), 20),
                "YELLOW SUBMARINE\x04\x04\x04\x04".as_bytes())
   }
+
+  #[test]
+  fn pkcs7_unpad() {
+    assert_eq!(unpad_pkcs7("ICE ICE BABY\x04\x04\x04\x04".as_bytes(), 16).unwrap(),
+               "ICE ICE BABY".as_bytes());
+    assert_eq!(invalid(unpad_pkcs7("ICE ICE BABY\x05\x05\x05\x05".as_bytes(), 16)),
+               PaddingError::BadFill {
+                 position: 11,
+                 byte: b'Y',
+               });
+    assert_eq!(invalid(unpad_pkcs7("ICE ICE BABY\x01\x02\x03\x04".as_bytes(), 16)),
+               PaddingError::BadFill {
+                 position: 12,
+                 byte: 1,
+               });
+    assert_eq!(invalid(unpad_pkcs7("ICE ICE BABY\x04\x04\x04\x00".as_bytes(), 16)),
+               PaddingError::BadLength(0));
+    assert_eq!(invalid(unpad_pkcs7("ICE ICE BABY\x04\x04\x04".as_bytes(), 16)),
+               PaddingError::NotBlockAligned(15));
+  }
+
+  fn round_trip<P: Padding>(scheme: P) {
+    for len in 0..33 {
+      let data: Vec<u8> = (1..len + 1).collect();
+      let padded = scheme.pad(&data, 16);
+      assert_eq!(padded.len() % 16, 0);
+      assert_eq!(scheme.unpad(&padded, 16).unwrap(), data);
+    }
+  }
+
+  #[test]
+  fn round_trips() {
+    round_trip(Pkcs7);
+    round_trip(AnsiX923);
+    round_trip(Iso7816);
+    round_trip(Iso10126);
+    round_trip(ZeroPadding);
+  }
+
+  #[test]
+  fn ansi_x923() {
+    assert_eq!(AnsiX923.pad("YELLOW".as_bytes(), 8), "YELLOW\x00\x02".as_bytes());
+    assert_eq!(invalid(AnsiX923.unpad("YELLOW\x01\x02".as_bytes(), 8)),
+               PaddingError::BadFill {
+                 position: 6,
+                 byte: 1,
+               });
+  }
+
+  #[test]
+  fn iso7816() {
+    assert_eq!(Iso7816.pad("YELLOW".as_bytes(), 8), &b"YELLOW\x80\x00"[..]);
+    assert_eq!(invalid(Iso7816.unpad("YELLOW\x00\x00".as_bytes(), 8)),
+               PaddingError::NoMarker);
+    assert_eq!(invalid(Iso7816.unpad(b"YELLOW\x80\x01", 8)),
+               PaddingError::NoMarker);
+  }
+
+  #[test]
+  fn iso10126() {
+    let padded = Iso10126.pad("YELLOW".as_bytes(), 8);
+    assert_eq!(padded[7], 2);
+    assert_eq!(invalid(Iso10126.unpad("YELLOW\x00\x09".as_bytes(), 8)),
+               PaddingError::BadLength(9));
+  }
+
+  #[test]
+  fn zero_padding() {
+    assert_eq!(ZeroPadding.pad("YELLOW".as_bytes(), 8), "YELLOW\x00\x00".as_bytes());
+    assert_eq!(ZeroPadding.pad("YELLOW S".as_bytes(), 8), "YELLOW S".as_bytes());
+  }
 }
@@ -0,0 +1,65 @@
+use rand::{self, Rng};
+use rand::distributions::{range, IndependentSample};
+
+pub mod mt19937;
+
+pub use self::mt19937::{Mt19937, Mt19937_64};
+
+pub fn byte_range(start: usize, end: usize) -> Vec<u8> {
+  let mut rand = rand::thread_rng();
+
+  let len_range = range::Range::new(start, end);
+  let len = len_range.ind_sample(&mut rand);
+
+  bytes(len)
+}
+
+pub fn bytes(len: usize) -> Vec<u8> {
+  bytes_from(&mut rand::thread_rng(), len)
+}
+
+/// Like `bytes`, but drawn from a generator of the caller's choosing.
+pub fn bytes_from<R: Rng>(rand: &mut R, len: usize) -> Vec<u8> {
+  let mut bytes = vec![0u8; len];
+  rand.fill_bytes(&mut bytes);
+  bytes
+}
+
+/// An endless stream of bytes from a generator, each output taken as four little-endian bytes -
+/// the same bytes `bytes_from` gives for an `Mt19937`.
+pub struct Keystream<R> {
+  rng: R,
+  word: u32,
+  left: u8,
+}
+
+pub fn keystream<R: Rng>(rng: R) -> Keystream<R> {
+  Keystream {
+    rng: rng,
+    word: 0,
+    left: 0,
+  }
+}
+
+impl<R: Rng> Iterator for Keystream<R> {
+  type Item = u8;
+
+  fn next(&mut self) -> Option<u8> {
+    if self.left == 0 {
+      self.word = self.rng.next_u32();
+      self.left = 4;
+    }
+    let byte = self.word as u8;
+    self.word >>= 8;
+    self.left -= 1;
+    Some(byte)
+  }
+}
+
+pub fn padding(input: &[u8]) -> Vec<u8> {
+  let mut padded = byte_range(5, 10);
+  padded.extend_from_slice(input);
+  let mut tail = byte_range(5, 10);
+  padded.append(&mut tail);
+  padded
+}
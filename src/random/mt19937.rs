@@ -0,0 +1,373 @@
+use rand::{Rng, SeedableRng};
+
+const N: usize = 624;
+const M: usize = 397;
+const MATRIX_A: u32 = 0x9908b0df;
+const UPPER_MASK: u32 = 0x80000000;
+const LOWER_MASK: u32 = 0x7fffffff;
+
+/// The 32 bit Mersenne Twister, as in Matsumoto and Nishimura's mt19937ar.c.
+///
+/// The state is exposed so that a generator can be rebuilt from observations of its output.
+#[derive(Clone)]
+pub struct Mt19937 {
+  state: [u32; N],
+  index: usize,
+}
+
+impl Mt19937 {
+  pub fn new(seed: u32) -> Mt19937 {
+    let mut state = [0u32; N];
+    state[0] = seed;
+    for i in 1..N {
+      state[i] = 1812433253u32.wrapping_mul(state[i - 1] ^ (state[i - 1] >> 30))
+        .wrapping_add(i as u32);
+    }
+    Mt19937 {
+      state: state,
+      index: N,
+    }
+  }
+
+  /// init_by_array from the reference implementation.  `key` mustn't be empty.
+  pub fn from_key(key: &[u32]) -> Mt19937 {
+    assert!(!key.is_empty(), "Mt19937::from_key needs at least one key word");
+    let mut mt = Mt19937::new(19650218);
+    let (mut i, mut j) = (1, 0);
+    for _ in 0..::std::cmp::max(N, key.len()) {
+      let prev = mt.state[i - 1] ^ (mt.state[i - 1] >> 30);
+      mt.state[i] = (mt.state[i] ^ prev.wrapping_mul(1664525))
+        .wrapping_add(key[j])
+        .wrapping_add(j as u32);
+      i += 1;
+      j += 1;
+      if i >= N {
+        mt.state[0] = mt.state[N - 1];
+        i = 1;
+      }
+      if j >= key.len() {
+        j = 0;
+      }
+    }
+    for _ in 0..N - 1 {
+      let prev = mt.state[i - 1] ^ (mt.state[i - 1] >> 30);
+      mt.state[i] = (mt.state[i] ^ prev.wrapping_mul(1566083941)).wrapping_sub(i as u32);
+      i += 1;
+      if i >= N {
+        mt.state[0] = mt.state[N - 1];
+        i = 1;
+      }
+    }
+    mt.state[0] = 0x80000000;
+    mt
+  }
+
+  /// A generator that will next twist `state` and then produce output from it.
+  pub fn from_state(state: [u32; N]) -> Mt19937 {
+    Mt19937 {
+      state: state,
+      index: N,
+    }
+  }
+
+  pub fn state(&self) -> &[u32; N] {
+    &self.state
+  }
+
+  /// How many outputs have been taken from the current state.
+  pub fn index(&self) -> usize {
+    self.index
+  }
+
+  pub fn temper(y: u32) -> u32 {
+    let mut y = y;
+    y ^= y >> 11;
+    y ^= (y << 7) & 0x9d2c5680;
+    y ^= (y << 15) & 0xefc60000;
+    y ^ (y >> 18)
+  }
+
+  /// Inverts `temper`, recovering a state word from an output.
+  pub fn untemper(y: u32) -> u32 {
+    let y = undo_right_shift(y, 18);
+    let y = undo_left_shift(y, 15, 0xefc60000);
+    let y = undo_left_shift(y, 7, 0x9d2c5680);
+    undo_right_shift(y, 11)
+  }
+
+  fn twist(&mut self) {
+    for i in 0..N {
+      let y = (self.state[i] & UPPER_MASK) | (self.state[(i + 1) % N] & LOWER_MASK);
+      let mag = if y & 1 == 0 { 0 } else { MATRIX_A };
+      self.state[i] = self.state[(i + M) % N] ^ (y >> 1) ^ mag;
+    }
+    self.index = 0;
+  }
+}
+
+/// Each pass fixes another `shift` bits, working down from the top.
+fn undo_right_shift(y: u32, shift: u32) -> u32 {
+  let mut x = y;
+  for _ in 0..32 / shift {
+    x = y ^ (x >> shift);
+  }
+  x
+}
+
+/// ... and up from the bottom.
+fn undo_left_shift(y: u32, shift: u32, mask: u32) -> u32 {
+  let mut x = y;
+  for _ in 0..32 / shift {
+    x = y ^ ((x << shift) & mask);
+  }
+  x
+}
+
+impl Rng for Mt19937 {
+  fn next_u32(&mut self) -> u32 {
+    if self.index >= N {
+      self.twist();
+    }
+    let y = self.state[self.index];
+    self.index += 1;
+    Mt19937::temper(y)
+  }
+
+  /// Each output becomes four little-endian bytes, in order.
+  fn fill_bytes(&mut self, dest: &mut [u8]) {
+    for chunk in dest.chunks_mut(4) {
+      let out = self.next_u32();
+      for (i, byte) in chunk.iter_mut().enumerate() {
+        *byte = (out >> (8 * i)) as u8;
+      }
+    }
+  }
+}
+
+impl SeedableRng<u32> for Mt19937 {
+  fn reseed(&mut self, seed: u32) {
+    *self = Mt19937::new(seed)
+  }
+
+  fn from_seed(seed: u32) -> Mt19937 {
+    Mt19937::new(seed)
+  }
+}
+
+const NN: usize = 312;
+const MM: usize = 156;
+const MATRIX_A_64: u64 = 0xb5026f5aa96619e9;
+const UPPER_MASK_64: u64 = 0xffffffff80000000;
+const LOWER_MASK_64: u64 = 0x7fffffff;
+
+/// The 64 bit Mersenne Twister, as in mt19937-64.c.
+///
+/// # Examples
+/// ```
+/// # extern crate rand;
+/// # extern crate cryptopals;
+/// # use rand::Rng;
+/// # use cryptopals::random::Mt19937_64;
+/// # fn main() {
+/// let mut mt = Mt19937_64::from_key(&[0x12345, 0x23456, 0x34567, 0x45678]);
+/// assert_eq!(mt.next_u64(), 7266447313870364031);
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Mt19937_64 {
+  state: [u64; NN],
+  index: usize,
+}
+
+impl Mt19937_64 {
+  pub fn new(seed: u64) -> Mt19937_64 {
+    let mut state = [0u64; NN];
+    state[0] = seed;
+    for i in 1..NN {
+      state[i] = 6364136223846793005u64.wrapping_mul(state[i - 1] ^ (state[i - 1] >> 62))
+        .wrapping_add(i as u64);
+    }
+    Mt19937_64 {
+      state: state,
+      index: NN,
+    }
+  }
+
+  /// init_by_array64 from the reference implementation.  `key` mustn't be empty.
+  pub fn from_key(key: &[u64]) -> Mt19937_64 {
+    assert!(!key.is_empty(), "Mt19937_64::from_key needs at least one key word");
+    let mut mt = Mt19937_64::new(19650218);
+    let (mut i, mut j) = (1, 0);
+    for _ in 0..::std::cmp::max(NN, key.len()) {
+      let prev = mt.state[i - 1] ^ (mt.state[i - 1] >> 62);
+      mt.state[i] = (mt.state[i] ^ prev.wrapping_mul(3935559000370003845))
+        .wrapping_add(key[j])
+        .wrapping_add(j as u64);
+      i += 1;
+      j += 1;
+      if i >= NN {
+        mt.state[0] = mt.state[NN - 1];
+        i = 1;
+      }
+      if j >= key.len() {
+        j = 0;
+      }
+    }
+    for _ in 0..NN - 1 {
+      let prev = mt.state[i - 1] ^ (mt.state[i - 1] >> 62);
+      mt.state[i] = (mt.state[i] ^ prev.wrapping_mul(2862933555777941757))
+        .wrapping_sub(i as u64);
+      i += 1;
+      if i >= NN {
+        mt.state[0] = mt.state[NN - 1];
+        i = 1;
+      }
+    }
+    mt.state[0] = 1 << 63;
+    mt
+  }
+
+  pub fn from_state(state: [u64; NN]) -> Mt19937_64 {
+    Mt19937_64 {
+      state: state,
+      index: NN,
+    }
+  }
+
+  pub fn state(&self) -> &[u64; NN] {
+    &self.state
+  }
+
+  pub fn index(&self) -> usize {
+    self.index
+  }
+
+  pub fn temper(y: u64) -> u64 {
+    let mut y = y;
+    y ^= (y >> 29) & 0x5555555555555555;
+    y ^= (y << 17) & 0x71d67fffeda60000;
+    y ^= (y << 37) & 0xfff7eee000000000;
+    y ^ (y >> 43)
+  }
+
+  fn twist(&mut self) {
+    for i in 0..NN {
+      let x = (self.state[i] & UPPER_MASK_64) | (self.state[(i + 1) % NN] & LOWER_MASK_64);
+      let mag = if x & 1 == 0 { 0 } else { MATRIX_A_64 };
+      self.state[i] = self.state[(i + MM) % NN] ^ (x >> 1) ^ mag;
+    }
+    self.index = 0;
+  }
+}
+
+impl Rng for Mt19937_64 {
+  fn next_u32(&mut self) -> u32 {
+    (self.next_u64() >> 32) as u32
+  }
+
+  fn next_u64(&mut self) -> u64 {
+    if self.index >= NN {
+      self.twist();
+    }
+    let x = self.state[self.index];
+    self.index += 1;
+    Mt19937_64::temper(x)
+  }
+}
+
+impl SeedableRng<u64> for Mt19937_64 {
+  fn reseed(&mut self, seed: u64) {
+    *self = Mt19937_64::new(seed)
+  }
+
+  fn from_seed(seed: u64) -> Mt19937_64 {
+    Mt19937_64::new(seed)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use rand::Rng;
+
+  // The first outputs listed in mt19937ar.out
+  #[test]
+  fn reference_by_array() {
+    let mut mt = Mt19937::from_key(&[0x123, 0x234, 0x345, 0x456]);
+    let outs: Vec<u32> = (0..5).map(|_| mt.next_u32()).collect();
+    assert_eq!(outs, vec![1067595299, 955945823, 477289528, 4107218783, 4228976476]);
+  }
+
+  // C++11 requires the 10000th output of a default constructed mt19937 (seed 5489) to be this.
+  #[test]
+  fn reference_seeded() {
+    let mut mt = Mt19937::new(5489);
+    assert_eq!(mt.next_u32(), 3499211612);
+    let last = (1..10000).map(|_| mt.next_u32()).last().unwrap();
+    assert_eq!(last, 4123659995);
+  }
+
+  // mt19937-64.out
+  #[test]
+  fn reference_64_by_array() {
+    let mut mt = Mt19937_64::from_key(&[0x12345, 0x23456, 0x34567, 0x45678]);
+    let outs: Vec<u64> = (0..5).map(|_| mt.next_u64()).collect();
+    assert_eq!(outs,
+               vec![7266447313870364031,
+                    4946485549665804864,
+                    16945909448695747420,
+                    16394063075524226720,
+                    4873882236456199058]);
+  }
+
+  // ... and the same for mt19937_64
+  #[test]
+  fn reference_64_seeded() {
+    let mut mt = Mt19937_64::new(5489);
+    let last = (0..10000).map(|_| mt.next_u64()).last().unwrap();
+    assert_eq!(last, 9981545732273789042);
+  }
+
+  #[test]
+  fn bytes_are_little_endian_outputs() {
+    use random::bytes_from;
+    let mut mt = Mt19937::new(5489);
+    assert_eq!(bytes_from(&mut mt, 6), vec![0x5c, 0xbb, 0x91, 0xd0, 0xf6, 0x9e]);
+  }
+
+  #[test]
+  fn untemper_inverts() {
+    let mut mt = Mt19937::new(1234);
+    for _ in 0..1000 {
+      let out = mt.next_u32();
+      assert_eq!(Mt19937::temper(Mt19937::untemper(out)), out);
+    }
+    assert_eq!(Mt19937::untemper(Mt19937::temper(0xffffffff)), 0xffffffff);
+  }
+
+  #[test]
+  fn state_round_trip() {
+    let mut mt = Mt19937::new(42);
+    mt.next_u32();
+    // The copy twists first, so it lines up with the source once that has used up this state
+    let mut copy = Mt19937::from_state(*mt.state());
+    for _ in 1..N {
+      mt.next_u32();
+    }
+    for _ in 0..2 * N {
+      assert_eq!(copy.next_u32(), mt.next_u32());
+    }
+  }
+
+  #[test]
+  #[should_panic(expected = "at least one key word")]
+  fn empty_key() {
+    Mt19937::from_key(&[]);
+  }
+
+  #[test]
+  #[should_panic(expected = "at least one key word")]
+  fn empty_key_64() {
+    Mt19937_64::from_key(&[]);
+  }
+}
@@ -11,9 +11,38 @@ pub enum CrackError {
   Base64(base64::FromBase64Error),
   Utf8(string::FromUtf8Error),
   Cipher(crypto::symmetriccipher::SymmetricCipherError),
+  Padding(PaddingError),
+  /// Decrypted text that wasn't ASCII, echoed back in the error as a careless server would
+  NonAscii(Vec<u8>),
   Str(&'static str),
 }
 
+/// Why a padded message was rejected.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PaddingError {
+  /// The message (of this length) isn't a whole number of blocks
+  NotBlockAligned(usize),
+  /// The trailing length byte is zero or longer than a block
+  BadLength(u8),
+  /// A byte inside the padding isn't what the scheme requires
+  BadFill { position: usize, byte: u8 },
+  /// No 0x80 marker ends the message (ISO/IEC 7816-4)
+  NoMarker,
+}
+
+impl fmt::Display for PaddingError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match *self {
+      PaddingError::NotBlockAligned(len) => write!(f, "{} bytes is not a whole number of blocks", len),
+      PaddingError::BadLength(b) => write!(f, "pad length byte {:#04x} is out of range", b),
+      PaddingError::BadFill { position, byte } => {
+        write!(f, "unexpected pad byte {:#04x} at {}", byte, position)
+      }
+      PaddingError::NoMarker => write!(f, "no padding marker"),
+    }
+  }
+}
+
 impl fmt::Display for CrackError {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
     match *self {
@@ -21,6 +50,8 @@ impl fmt::Display for CrackError {
       CrackError::Io(ref e) => write!(f, "IO: {}", e),
       CrackError::Base64(ref e) => write!(f, "Base64: {}", e),
       CrackError::Utf8(ref e) => write!(f, "Utf8: {}", e),
+      CrackError::Padding(ref e) => write!(f, "Invalid padding: {}", e),
+      CrackError::NonAscii(ref plain) => write!(f, "Non-ASCII plaintext: {:?}", plain),
       CrackError::Str(ref e) => write!(f, "{}", e),
     }
   }
@@ -33,6 +64,8 @@ impl error::Error for CrackError {
       CrackError::Io(ref e) => e.description(),
       CrackError::Base64(ref e) => e.description(),
       CrackError::Utf8(ref e) => e.description(),
+      CrackError::Padding(_) => "Invalid padding",
+      CrackError::NonAscii(_) => "Non-ASCII plaintext",
       CrackError::Str(ref e) => e,
     }
   }
@@ -43,6 +76,8 @@ impl error::Error for CrackError {
       CrackError::Io(ref e) => Some(e),
       CrackError::Base64(ref e) => Some(e),
       CrackError::Utf8(ref e) => Some(e),
+      CrackError::Padding(_) => None,
+      CrackError::NonAscii(_) => None,
       CrackError::Str(_) => None,
     }
   }
@@ -63,6 +98,11 @@ impl From<string::FromUtf8Error> for CrackError {
     CrackError::Utf8(e)
   }
 }
+impl From<PaddingError> for CrackError {
+  fn from(e: PaddingError) -> CrackError {
+    CrackError::Padding(e)
+  }
+}
 impl From<&'static str> for CrackError {
   fn from(e: &'static str) -> CrackError {
     CrackError::Str(e)
@@ -48,7 +48,8 @@ fn pick_keysize(crypted: &[u8]) -> Result<usize, CrackError> {
     .ok_or(CrackError::Str("empty keysize range"))
 }
 
-fn get_slice(crypted: &[u8], offset: usize, keysize: usize) -> Vec<u8> {
+/// Every `keysize`th byte, starting at `offset`: the bytes that met the same key byte.
+pub fn get_slice(crypted: &[u8], offset: usize, keysize: usize) -> Vec<u8> {
   crypted.iter()
     .enumerate()
     .filter_map(|(i, c)| {
@@ -62,13 +63,17 @@ fn get_slice(crypted: &[u8], offset: usize, keysize: usize) -> Vec<u8> {
 }
 
 fn key_for_slice(crypted: &[u8], offset: usize, keysize: usize) -> Option<u8> {
+  scored_key_for_slice(crypted, offset, keysize).map(|(_sc, key)| key)
+}
+
+/// The likeliest key byte for one slice, with its score (lower is more English-like).
+pub fn scored_key_for_slice(crypted: &[u8], offset: usize, keysize: usize) -> Option<(u32, u8)> {
   let slice = get_slice(crypted, offset, keysize);
   frequency::Counts::new(slice)
     .most_congruent_item(&(*frequency::ENGLISH_FREQS),
                          &(*frequency::ENGLISH_PENALTIES),
                          25,
                          |a, b| a ^ b)
-    .map(|(_sc, key)| key)
 }
 
 #[cfg(test)]
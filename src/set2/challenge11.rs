@@ -2,6 +2,7 @@ use aes::{ecb, cbc};
 use result::Result;
 use rand::{self, Rng};
 
+use oracle::EncryptOracle;
 use padding;
 use random;
 
@@ -36,40 +37,35 @@ pub fn encryption_oracle(input: &[u8]) -> Result<Vec<u8>> {
   let mut rng = rand::thread_rng();
 
   let oracle = if rng.gen() {
-    pick_encryption_oracle(Mode::ElectronicCodebook)
+    ModeOracle(Mode::ElectronicCodebook)
   } else {
-    pick_encryption_oracle(Mode::CipherBlockChaining)
+    ModeOracle(Mode::CipherBlockChaining)
   };
 
-  oracle(input)
+  oracle.encrypt(input)
 }
 
-fn pick_encryption_oracle(kind: Mode) -> fn(&[u8]) -> Result<Vec<u8>> {
-  match kind {
-    Mode::ElectronicCodebook => ecb_encryption_oracle,
-    Mode::CipherBlockChaining => cbc_encryption_oracle,
-  }
-}
+/// Encrypts under a fresh random key (and IV), with random bytes either side of the input.
+struct ModeOracle(Mode);
 
-fn cbc_encryption_oracle(your_input: &[u8]) -> Result<Vec<u8>> {
-  cbc::encrypt(&random::bytes(16),
-               &random::bytes(16),
-               &padding::pkcs7(&random::padding(your_input), 16))
-}
-
-fn ecb_encryption_oracle(your_input: &[u8]) -> Result<Vec<u8>> {
-  ecb::encrypt(&random::bytes(16),
-               &padding::pkcs7(&random::padding(your_input), 16))
+impl EncryptOracle for ModeOracle {
+  fn encrypt(&self, your_input: &[u8]) -> Result<Vec<u8>> {
+    let padded = padding::pkcs7(&random::padding(your_input), 16);
+    match self.0 {
+      Mode::ElectronicCodebook => ecb::encrypt(&random::bytes(16), &padded),
+      Mode::CipherBlockChaining => cbc::encrypt(&random::bytes(16), &random::bytes(16), &padded),
+    }
+  }
 }
 
 #[cfg(test)]
 mod test {
-  use super::pick_encryption_oracle;
+  use super::ModeOracle;
   use analysis::Mode;
   use analysis::aes::detector;
 
   fn matching_mode(mode: Mode) {
-    assert_eq!(detector(16, pick_encryption_oracle(mode)).unwrap(), mode);
+    assert_eq!(detector(16, &ModeOracle(mode)).unwrap(), mode);
   }
 
   #[test]
@@ -2,6 +2,7 @@ use serialize::base64::FromBase64;
 use result::{Result, CrackError};
 
 use aes::ecb;
+use oracle::EncryptOracle;
 use random;
 use padding;
 
@@ -42,6 +43,50 @@ mod test {
 
     assert!(cracked.find("waving just").is_some())
   }
+
+  #[test]
+  fn aes256() {
+    let oracle = super::EncryptionOracle::with_key_size("d2F2aW5nIGp1c3QgdG8gc2F5IGhp".as_bytes(), 32);
+    let mut supplicant = super::Supplicant::new(&oracle);
+    supplicant.interrogate().unwrap();
+    assert_eq!(supplicant.decrypted, "waving just to say hi".as_bytes())
+  }
+
+  #[test]
+  fn closure_oracle() {
+    use aes::ecb;
+    use oracle::EncryptFn;
+    use padding;
+    use result::Result;
+
+    let oracle = EncryptFn(|input: &[u8]| -> Result<Vec<u8>> {
+      ecb::encrypt("YELLOW SUBMARINE".as_bytes(),
+                   &padding::pkcs7(&[input, "rollin".as_bytes()].concat(), 16))
+    });
+    let mut supplicant = super::Supplicant::new(&oracle);
+    supplicant.interrogate().unwrap();
+    assert_eq!(supplicant.decrypted, "rollin".as_bytes())
+  }
+
+  #[test]
+  fn prefix_ending_in_filler() {
+    use aes::ecb;
+    use oracle::EncryptFn;
+    use padding;
+    use result::Result;
+
+    let repeating = [&[b'x'; 32][..], b"yz"].concat();
+    for prefix in &[&b"a"[..], b"0123456789abcdefb", b"aaaaaaaaaaaaaaaa", b"xyzab", &repeating] {
+      let oracle = EncryptFn(|input: &[u8]| -> Result<Vec<u8>> {
+        ecb::encrypt("YELLOW SUBMARINE".as_bytes(),
+                     &padding::pkcs7(&[prefix, input, "abba rollin".as_bytes()].concat(), 16))
+      });
+      let mut supplicant = super::Supplicant::new(&oracle);
+      supplicant.interrogate().unwrap();
+      assert_eq!(supplicant.prefix_size(), prefix.len());
+      assert_eq!(supplicant.decrypted, "abba rollin".as_bytes())
+    }
+  }
 }
 
 pub fn solve(b64msg: &[u8]) -> String {
@@ -51,71 +96,112 @@ pub fn solve(b64msg: &[u8]) -> String {
   String::from_utf8(supplicant.decrypted).unwrap()
 }
 
-struct EncryptionOracle {
+pub struct EncryptionOracle {
   key: Vec<u8>,
+  prefix: Vec<u8>,
   message: Vec<u8>,
 }
 
 impl EncryptionOracle {
-  fn new(msg: &[u8]) -> EncryptionOracle {
+  pub fn new(msg: &[u8]) -> EncryptionOracle {
+    EncryptionOracle::with_key_size(msg, 16)
+  }
+
+  pub fn with_key_size(msg: &[u8], key_size: usize) -> EncryptionOracle {
     let b64msg = msg.clone();
     let msg = b64msg.from_base64().unwrap();
     EncryptionOracle {
-      key: random::bytes(16),
+      key: random::bytes(key_size),
+      prefix: vec![],
       message: msg,
     }
   }
 
-  pub fn advise(&self, input: &[u8]) -> Result<Vec<u8>> {
+  /// Like `new`, but every encryption is prefixed with the same random bytes (of random length).
+  pub fn with_random_prefix(msg: &[u8]) -> EncryptionOracle {
+    let mut oracle = EncryptionOracle::new(msg);
+    oracle.prefix = random::byte_range(0, 64);
+    oracle
+  }
+}
+
+impl EncryptOracle for EncryptionOracle {
+  fn encrypt(&self, input: &[u8]) -> Result<Vec<u8>> {
     ecb::encrypt(&self.key,
-                 &padding::pkcs7(&[input, &self.message].concat(), 16))
+                 &padding::pkcs7(&[&self.prefix, input, &self.message].concat(), 16))
   }
 }
 
-struct Supplicant<'g> {
+pub struct Supplicant<'g, O: EncryptOracle + ?Sized + 'g> {
   block_size: usize,
   cipher_mode: Mode,
   target_crypt: Vec<u8>,
   target_size: usize,
+  prefix_size: usize,
   decrypted: Vec<u8>,
-  oracle: &'g EncryptionOracle,
+  oracle: &'g O,
 }
 
-use std::iter::{once, repeat};
+use std::iter::{once, repeat_n};
 use utils::full_u8;
 use analysis::{self, Mode};
 
-impl<'g> Supplicant<'g> {
-  fn new(oracle: &EncryptionOracle) -> Supplicant {
+impl<'g, O: EncryptOracle + ?Sized> Supplicant<'g, O> {
+  pub fn new(oracle: &'g O) -> Supplicant<'g, O> {
     Supplicant {
       block_size: 16,
       cipher_mode: Mode::CipherBlockChaining,
       target_crypt: vec![],
       target_size: 0,
+      prefix_size: 0,
       decrypted: vec![],
       oracle: oracle,
     }
   }
 
-  fn interrogate(&mut self) -> Result<&Supplicant> {
-    let (bs, ts) = try!(self.block_period());
+  pub fn decrypted(&self) -> &[u8] {
+    &self.decrypted
+  }
+
+  pub fn block_size(&self) -> usize {
+    self.block_size
+  }
+
+  pub fn prefix_size(&self) -> usize {
+    self.prefix_size
+  }
+
+  pub fn target_size(&self) -> usize {
+    self.target_size
+  }
+
+  /// Works out the block size, mode, and how many bytes the oracle puts before and after our
+  /// input, without decrypting anything.
+  pub fn survey(&mut self) -> Result<&Supplicant<'g, O>> {
+    let (bs, fixed_size) = try!(self.block_period());
     self.block_size = bs;
-    self.target_size = ts;
     self.cipher_mode = try!(self.detect_mode());
-    self.target_crypt = try!(self.oracle.advise(&[][0..0]));
+    self.prefix_size = try!(self.find_prefix_size());
+    self.target_size = fixed_size - self.prefix_size;
+    Ok(self)
+  }
+
+  pub fn interrogate(&mut self) -> Result<&Supplicant<'g, O>> {
+    try!(self.survey());
+    self.target_crypt = try!(self.oracle.encrypt(&self.alignment()));
     for _ in 0..self.target_size {
       let next_byte = try!(self.build_dict(&self.decrypted));
       self.decrypted.push(next_byte)
     }
-    println!("Validation!");
     self.validate()
   }
 
-  fn validate(&self) -> Result<&Supplicant> {
-    let trial = &padding::pkcs7(&self.decrypted, self.block_size);
-    let prophecy = &try!(self.oracle.advise(trial));
-    let check = &prophecy[0..self.target_crypt.len()];
-    let against = &self.target_crypt[..];
+  fn validate(&self) -> Result<&Supplicant<'g, O>> {
+    let trial = &[self.alignment(), padding::pkcs7(&self.decrypted, self.block_size)].concat();
+    let prophecy = &try!(self.oracle.encrypt(trial));
+    let skip = self.controlled_start();
+    let check = &prophecy[skip..self.target_crypt.len()];
+    let against = &self.target_crypt[skip..];
 
     if check == against {
       Ok(self)
@@ -124,20 +210,33 @@ impl<'g> Supplicant<'g> {
     }
   }
 
+  /// Filler that pushes our input up to the next block boundary after the prefix.
+  fn alignment(&self) -> Vec<u8> {
+    let align = (self.block_size - self.prefix_size % self.block_size) % self.block_size;
+    repeat_n(b'a', align).collect()
+  }
+
+  /// Offset of the first block that starts with bytes we control.
+  fn controlled_start(&self) -> usize {
+    self.prefix_size + self.alignment().len()
+  }
+
   fn build_dict(&self, known: &[u8]) -> Result<u8> {
-    let target_block_start = (known.len() / self.block_size) * self.block_size;
+    let target_block_start = self.controlled_start() +
+                             (known.len() / self.block_size) * self.block_size;
     let target_block_end = target_block_start + self.block_size;
     let target_block_range = target_block_start..target_block_end;
-    let target_known_bytes = known.len() - target_block_start;
+    let target_known_bytes = known.len() % self.block_size;
+    let alignment = self.alignment();
     let a = &b'a';
-    let shim = repeat(a).take(self.block_size - target_known_bytes - 1);
+    let shim = alignment.iter().chain(repeat_n(a, self.block_size - target_known_bytes - 1));
 
     let msg: Vec<u8> = shim.clone().cloned().collect();
-    let target = &try!(self.oracle.advise(&msg))[target_block_range.clone()];
+    let target = &try!(self.oracle.encrypt(&msg))[target_block_range.clone()];
 
     for c in full_u8() {
       let trial: Vec<u8> = shim.clone().chain(known).cloned().chain(once(c)).collect();
-      let prophecy = &try!(self.oracle.advise(&trial))[target_block_range.clone()];
+      let prophecy = &try!(self.oracle.encrypt(&trial))[target_block_range.clone()];
 
       if prophecy == target {
         return Ok(c);
@@ -146,22 +245,48 @@ impl<'g> Supplicant<'g> {
     return Err(CrackError::Str("No byte satisfies!"));
   }
 
-  fn block_period(&self) -> Result<(usize, usize)> {
-    let base_size = try!(self.oracle.advise(&[][0..0])).len();
+  /// Returns the block size, and the combined length of whatever the oracle wraps around our
+  /// input.
+  pub fn block_period(&self) -> Result<(usize, usize)> {
+    let base_size = try!(self.oracle.encrypt(&[][0..0])).len();
     for n in 1..64 {
-      let size = try!(self.oracle.advise(&(repeat(b'a').take(n).collect::<Vec<_>>()))).len();
+      let size = try!(self.oracle.encrypt(&(repeat_n(b'a', n).collect::<Vec<_>>()))).len();
       if size != base_size {
-        println!("{} {} {}", base_size, size, n);
-        println!("{} {}", size - base_size, base_size - n);
-
         return Ok((size - base_size, base_size - n));
       }
     }
     Err(CrackError::Str("No block periodicity detected"))
   }
 
+  /// Finds how many bytes the oracle puts ahead of our input, via aligned duplicate blocks:
+  /// after `pad` filler bytes, two copies of a marker block encrypt to two identical blocks once
+  /// the filler finishes off the prefix's last block.
+  ///
+  /// Two printable markers are sent that share no byte with each other or the filler, and the
+  /// duplicate only counts if it sits at the same block in both and differs between them - so
+  /// repeats already in the prefix or secret, or a prefix ending in bytes that look like filler,
+  /// can't pass for our input lining up.
+  fn find_prefix_size(&self) -> Result<usize> {
+    let bs = self.block_size;
+    let low: Vec<u8> = (0..bs).map(|b| b'A' + (b % 26) as u8).collect();
+    let high: Vec<u8> = (0..bs).map(|b| b'b' + (b % 26) as u8).collect();
+    for pad in 0..bs {
+      let filler: Vec<u8> = repeat_n(b'a', pad).collect();
+      let crypt_low = try!(self.oracle.encrypt(&[&filler[..], &low, &low].concat()));
+      let crypt_high = try!(self.oracle.encrypt(&[&filler[..], &high, &high].concat()));
+      let low_blocks: Vec<&[u8]> = crypt_low.chunks(bs).collect();
+      let high_blocks: Vec<&[u8]> = crypt_high.chunks(bs).collect();
+      for i in 1..low_blocks.len().min(high_blocks.len()) {
+        if low_blocks[i - 1] == low_blocks[i] && high_blocks[i - 1] == high_blocks[i] &&
+           low_blocks[i] != high_blocks[i] {
+          return Ok((i - 1) * bs - pad);
+        }
+      }
+    }
+    Err(CrackError::Str("Couldn't align to the prefix"))
+  }
+
   fn detect_mode(&self) -> Result<Mode> {
-    let oracle_fn = |msg: &[u8]| self.oracle.advise(msg);
-    analysis::aes::detector(self.block_size, oracle_fn)
+    analysis::aes::detector(self.block_size, self.oracle)
   }
 }
@@ -0,0 +1,195 @@
+use std::iter::repeat_n;
+use aes::ecb;
+use oracle::EncryptOracle;
+use padding;
+use random;
+use result::{Result, CrackError};
+use super::challenge12::Supplicant;
+
+/// ECB cut-and-paste
+/// Write a k=v parsing routine, as if for a structured cookie. The routine should take:
+///
+/// foo=bar&baz=qux&zap=zazzle
+///
+/// ... and produce:
+///
+/// {
+///   foo: 'bar',
+///   baz: 'qux',
+///   zap: 'zazzle'
+/// }
+///
+/// (you know, the object; I don't care if you convert it to JSON).
+///
+/// Now write a function that encodes a user profile in that format, given an email address. You
+/// should have something like:
+///
+/// profile_for("foo@bar.com")
+///
+/// ... and it should produce:
+///
+/// {
+///   email: 'foo@bar.com',
+///   uid: 10,
+///   role: 'user'
+/// }
+///
+/// ... encoded as:
+///
+/// email=foo@bar.com&uid=10&role=user
+///
+/// Your "profile_for" function should not allow encoding metacharacters (& and =). Eat them, quote
+/// them, whatever you want to do, but don't let people set their email address to
+/// "foo@bar.com&role=admin".
+///
+/// Now, two more easy functions. Generate a random AES key, then:
+///
+/// Encrypt the encoded user profile under the key; "provide" that to the "attacker".
+/// Decrypt the encoded user profile and parse it.
+///
+/// Using only the user input to profile_for() (as an oracle to generate "valid" ciphertexts) and
+/// the ciphertexts themselves, make a role=admin profile.
+///
+/// # Examples
+/// ```
+/// use cryptopals::set2::challenge13::*;
+/// assert_eq!(profile_for("foo@bar.com&role=admin"),
+///            "email=foo@bar.comroleadmin&uid=10&role=user");
+///
+/// let oracle = ProfileOracle::new();
+/// let forged = forge_admin(&oracle).unwrap();
+/// assert_eq!(oracle.role(&forged).unwrap(), "admin");
+/// ```
+pub fn parse(cookie: &str) -> Result<Vec<(String, String)>> {
+  cookie.split('&')
+    .map(|pair| {
+      let mut kv = pair.splitn(2, '=');
+      match (kv.next(), kv.next()) {
+        (Some(k), Some(v)) if !k.is_empty() => Ok((String::from(k), String::from(v))),
+        _ => Err(CrackError::Str("malformed k=v pair")),
+      }
+    })
+    .collect()
+}
+
+pub fn encode(fields: &[(String, String)]) -> String {
+  fields.iter()
+    .map(|(k, v)| format!("{}={}", k, v))
+    .collect::<Vec<_>>()
+    .join("&")
+}
+
+pub fn profile_for(email: &str) -> String {
+  let email: String = email.chars().filter(|&c| c != '&' && c != '=').collect();
+  encode(&[(String::from("email"), email),
+           (String::from("uid"), String::from("10")),
+           (String::from("role"), String::from("user"))])
+}
+
+/// Hands out encrypted profiles for whatever email it's given, and reads them back.
+pub struct ProfileOracle {
+  key: Vec<u8>,
+}
+
+impl ProfileOracle {
+  pub fn new() -> ProfileOracle {
+    ProfileOracle { key: random::bytes(16) }
+  }
+
+  pub fn profile(&self, crypted: &[u8]) -> Result<Vec<(String, String)>> {
+    let padded = try!(ecb::decrypt(&self.key, crypted));
+    let plain = try!(String::from_utf8(try!(padding::unpad_pkcs7(&padded, 16))));
+    parse(&plain)
+  }
+
+  pub fn role(&self, crypted: &[u8]) -> Result<String> {
+    try!(self.profile(crypted))
+      .into_iter()
+      .find(|(k, _)| k == "role")
+      .map(|(_, v)| v)
+      .ok_or(CrackError::Str("profile has no role"))
+  }
+}
+
+impl Default for ProfileOracle {
+  fn default() -> ProfileOracle {
+    ProfileOracle::new()
+  }
+}
+
+impl EncryptOracle for ProfileOracle {
+  fn encrypt(&self, email: &[u8]) -> Result<Vec<u8>> {
+    let email = try!(String::from_utf8(email.to_vec()));
+    ecb::encrypt(&self.key, &padding::pkcs7(profile_for(&email).as_bytes(), 16))
+  }
+}
+
+/// Splices together a ciphertext that decrypts to a `role=admin` profile.
+///
+/// The attack assumes only that the profile ends with the role value `user`: one ciphertext gets
+/// `role=` to end a block, and another supplies a block that is just `admin` plus its padding.
+pub fn forge_admin<O: EncryptOracle + ?Sized>(oracle: &O) -> Result<Vec<u8>> {
+  let mut supplicant = Supplicant::new(oracle);
+  try!(supplicant.survey());
+  let bs = supplicant.block_size();
+  let prefix = supplicant.prefix_size();
+  let suffix = supplicant.target_size();
+
+  // The admin block, lined up to start just after the prefix
+  let align = (bs - prefix % bs) % bs;
+  let lure: Vec<u8> = repeat_n(b'a', align)
+    .chain(padding::pkcs7("admin".as_bytes(), bs))
+    .collect();
+  let admin_start = prefix + align;
+  let admin_block = try!(oracle.encrypt(&lure))[admin_start..admin_start + bs].to_vec();
+
+  // An email long enough to push "user" into a block of its own
+  let head = prefix + suffix - "user".len();
+  let email: Vec<u8> = repeat_n(b'a', (bs - head % bs) % bs + bs - "@bar.com".len())
+    .chain("@bar.com".bytes())
+    .collect();
+  let crypted = try!(oracle.encrypt(&email));
+  let cut = head + email.len();
+
+  Ok([&crypted[..cut], &admin_block[..]].concat())
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn parses() {
+    assert_eq!(parse("foo=bar&baz=qux&zap=zazzle").unwrap(),
+               vec![(String::from("foo"), String::from("bar")),
+                    (String::from("baz"), String::from("qux")),
+                    (String::from("zap"), String::from("zazzle"))]);
+    assert_eq!(parse("a=b=c").unwrap(),
+               vec![(String::from("a"), String::from("b=c"))]);
+    assert!(parse("foo=bar&baz").is_err());
+    assert!(parse("=bar").is_err());
+  }
+
+  #[test]
+  fn round_trip() {
+    let profile = profile_for("foo@bar.com");
+    assert_eq!(profile, "email=foo@bar.com&uid=10&role=user");
+    assert_eq!(encode(&parse(&profile).unwrap()), profile);
+  }
+
+  #[test]
+  fn honest_profiles() {
+    let oracle = ProfileOracle::new();
+    let crypted = oracle.encrypt("foo@bar.com&role=admin".as_bytes()).unwrap();
+    assert_eq!(oracle.role(&crypted).unwrap(), "user");
+  }
+
+  #[test]
+  fn forges_admin() {
+    let oracle = ProfileOracle::new();
+    let forged = forge_admin(&oracle).unwrap();
+    let profile = oracle.profile(&forged).unwrap();
+    assert_eq!(profile[1], (String::from("uid"), String::from("10")));
+    assert_eq!(profile[2], (String::from("role"), String::from("admin")));
+  }
+}
@@ -0,0 +1,40 @@
+use super::challenge12::{EncryptionOracle, Supplicant};
+
+/// Byte-at-a-time ECB decryption (Harder)
+/// Take your oracle function from #12. Now generate a random count of random bytes and prepend
+/// this string to every plaintext. You are now doing:
+///
+/// AES-128-ECB(random-prefix || attacker-controlled || target-bytes, random-key)
+///
+/// Same goal: decrypt the target-bytes.
+///
+/// Stop and think for a second.
+/// What's harder than challenge #12 about doing this? How would you overcome that obstacle? The
+/// hint is: you're using all the tools you already have; no crazy math is required.
+///
+/// Think "STIMULUS" and "RESPONSE".
+pub fn solve(b64msg: &[u8]) -> String {
+  let oracle = EncryptionOracle::with_random_prefix(b64msg);
+  let mut supplicant = Supplicant::new(&oracle);
+  supplicant.interrogate().unwrap();
+  String::from_utf8(supplicant.decrypted().to_vec()).unwrap()
+}
+
+#[cfg(test)]
+mod test {
+  #[test]
+  fn can_solve() {
+    for _ in 0..2 {
+      let cracked = super::solve("Um9sbGluJyBpbiBteSA1LjAKV2l0aCBteSByYWctdG9wIGRvd24gc28gbXkgaGFpciBjYW4gYmxvdwpUaGUgZ2lybGllcyBvbiBzdGFuZGJ5IHdhdmluZyBqdXN0IHRvIHNheSBoaQpEaWQgeW91IHN0b3A/IE5vLCBJIGp1c3QgZHJvdmUgYnkK".as_bytes());
+
+      assert!(cracked.find("waving just").is_some())
+    }
+  }
+
+  #[test]
+  fn secret_starting_with_filler() {
+    // "aaaardvark"
+    let cracked = super::solve("YWFhYXJkdmFyaw==".as_bytes());
+    assert_eq!(cracked, "aaaardvark")
+  }
+}
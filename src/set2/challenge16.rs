@@ -0,0 +1,152 @@
+use std::iter::repeat_n;
+use aes::cbc;
+use analysis;
+use oracle::EncryptOracle;
+use padding;
+use random;
+use result::{Result, CrackError};
+use super::challenge12::Supplicant;
+
+pub fn quote(input: &[u8]) -> Vec<u8> {
+  let mut quoted = vec![];
+  for &b in input {
+    match b {
+      b';' => quoted.extend_from_slice(b"%3B"),
+      b'=' => quoted.extend_from_slice(b"%3D"),
+      b'%' => quoted.extend_from_slice(b"%25"),
+      _ => quoted.push(b),
+    }
+  }
+  quoted
+}
+
+pub fn wrap_comment(input: &[u8]) -> Vec<u8> {
+  [&b"comment1=cooking%20MCs;userdata="[..],
+   &quote(input),
+   &b";comment2=%20like%20a%20pound%20of%20bacon"[..]]
+    .concat()
+}
+
+/// Looks for an `admin=true` field in a `;` separated cookie.
+pub fn has_admin(cookie: &[u8]) -> bool {
+  cookie.split(|&b| b == b';').any(|field| field == b"admin=true")
+}
+
+/// CBC bitflipping attacks
+/// Generate a random AES key.
+///
+/// Combine your padding code and CBC code to write two functions.
+///
+/// The first function should take an arbitrary input string, prepend the string:
+///
+/// "comment1=cooking%20MCs;userdata="
+///
+/// .. and append the string:
+///
+/// ";comment2=%20like%20a%20pound%20of%20bacon"
+///
+/// The function should quote out the ";" and "=" characters.
+///
+/// The function should then pad out the input to the 16-byte AES block length and encrypt it under
+/// the random AES key.
+///
+/// The second function should decrypt the string and look for the characters ";admin=true;" (or,
+/// equivalently, decrypt, split the string on ";", convert each resulting string into 2-tuples,
+/// and look for the "admin" tuple).
+///
+/// Return true or false based on whether the string exists.
+///
+/// If you've written the first function properly, it should not be possible to provide user input
+/// to it that will generate the string the second function is looking for. We'll have to break
+/// the crypto to do that.
+///
+/// Instead, modify the ciphertext (without knowledge of the AES key) to accomplish this.
+///
+/// You're relying on the fact that in CBC mode, a 1-bit error in a ciphertext block:
+///
+/// Completely scrambles the block the error occurs in
+/// Produces the identical 1-bit error(/edit) in the next ciphertext block.
+///
+/// # Examples
+/// ```
+/// use cryptopals::set2::challenge16::*;
+/// let oracle = CommentOracle::new();
+/// let forged = make_admin(&oracle).unwrap();
+/// assert!(oracle.is_admin(&forged).unwrap());
+/// ```
+pub struct CommentOracle {
+  key: Vec<u8>,
+  iv: Vec<u8>,
+}
+
+impl CommentOracle {
+  pub fn new() -> CommentOracle {
+    CommentOracle {
+      key: random::bytes(16),
+      iv: random::bytes(16),
+    }
+  }
+
+  pub fn is_admin(&self, crypted: &[u8]) -> Result<bool> {
+    let padded = try!(cbc::decrypt(&self.key, &self.iv, crypted));
+    Ok(has_admin(&try!(padding::unpad_pkcs7(&padded, 16))))
+  }
+}
+
+impl Default for CommentOracle {
+  fn default() -> CommentOracle {
+    CommentOracle::new()
+  }
+}
+
+impl EncryptOracle for CommentOracle {
+  fn encrypt(&self, input: &[u8]) -> Result<Vec<u8>> {
+    cbc::encrypt(&self.key, &self.iv, &padding::pkcs7(&wrap_comment(input), 16))
+  }
+}
+
+/// Three blocks of filler guarantee two whole blocks of it after the block our input starts in:
+/// the first is sacrificed to flip the second into `;admin=true;`.  The block size comes from
+/// watching the ciphertext grow, so any CBC format with blocks of 12 bytes or more will do.
+pub fn make_admin<O: EncryptOracle + ?Sized>(oracle: &O) -> Result<Vec<u8>> {
+  let (bs, _) = try!(Supplicant::new(oracle).block_period());
+  let mut desired = b";admin=true;".to_vec();
+  if desired.len() > bs {
+    return Err(CrackError::Str("blocks are too small to hold ;admin=true;"));
+  }
+  desired.resize(bs, b'A');
+  // Changing the input changes the block it starts in, and nothing before it
+  let start = try!(analysis::first_difference(oracle, b"A", b"B", bs));
+  let filler: Vec<u8> = repeat_n(b'A', 3 * bs).collect();
+  let crypted = try!(oracle.encrypt(&filler));
+  analysis::cbc::bitflip(&crypted, bs, start + 2, &filler[0..bs], &desired)
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use oracle::EncryptOracle;
+
+  #[test]
+  fn quotes_metacharacters() {
+    assert_eq!(quote(b"a;admin=true;%"), b"a%3Badmin%3Dtrue%3B%25".to_vec());
+    let oracle = CommentOracle::new();
+    let crypted = oracle.encrypt(b";admin=true;").unwrap();
+    assert!(!oracle.is_admin(&crypted).unwrap());
+  }
+
+  #[test]
+  fn finds_admin() {
+    assert!(has_admin(b"comment1=x;admin=true;comment2=y"));
+    assert!(!has_admin(b"comment1=x;admin=true2;comment2=y"));
+  }
+
+  #[test]
+  fn flips_to_admin() {
+    for _ in 0..8 {
+      let oracle = CommentOracle::new();
+      let forged = make_admin(&oracle).unwrap();
+      assert!(oracle.is_admin(&forged).unwrap());
+    }
+  }
+}
@@ -0,0 +1,126 @@
+use serialize::base64::FromBase64;
+use rand::{self, Rng};
+use aes::cbc;
+use oracle::PaddingOracle;
+use padding;
+use random;
+use result::Result;
+
+static LINES: [&str; 10] =
+  ["MDAwMDAwTm93IHRoYXQgdGhlIHBhcnR5IGlzIGp1bXBpbmc=",
+   "MDAwMDAxV2l0aCB0aGUgYmFzcyBraWNrZWQgaW4gYW5kIHRoZSBWZWdhJ3MgYXJlIHB1bXBpbic=",
+   "MDAwMDAyUXVpY2sgdG8gdGhlIHBvaW50LCB0byB0aGUgcG9pbnQsIG5vIGZha2luZw==",
+   "MDAwMDAzQ29va2luZyBNQydzIGxpa2UgYSBwb3VuZCBvZiBiYWNvbg==",
+   "MDAwMDA0QnVybmluZyAnZW0sIGlmIHlvdSBhaW4ndCBxdWljayBhbmQgbmltYmxl",
+   "MDAwMDA1SSBnbyBjcmF6eSB3aGVuIEkgaGVhciBhIGN5bWJhbCBhbmQgYSBoaSBoYXQ=",
+   "MDAwMDA2QW5kIGEgaGlnaCBoYXQgd2l0aCBhIHNvdXBlZCB1cCB0ZW1wbw==",
+   "MDAwMDA3SSdtIG9uIGEgcm9sbCwgaXQncyB0aW1lIHRvIGdvIHNvbG8=",
+   "MDAwMDA4b2xsaW4nIGluIG15IGZpdmUgcG9pbnQgb2g=",
+   "MDAwMDA5aXRoIG15IHJhZy10b3AgZG93biBzbyBteSBoYWlyIGNhbiBibG93"];
+
+/// The CBC padding oracle
+/// This is the best-known attack on modern block-cipher cryptography.
+///
+/// Combine your padding code and your CBC code to write two functions.
+///
+/// The first function should select at random one of the following 10 strings, generate a random
+/// AES key (which it should save for all future encryptions), pad the string out to the 16-byte
+/// AES block size and CBC-encrypt it under that key, providing the caller the ciphertext and IV.
+///
+/// The second function should consume the ciphertext produced by the first function, decrypt it,
+/// check its padding, and return true or false depending on whether the padding is valid.
+///
+/// It turns out that it's possible to decrypt the ciphertexts provided by the first function.
+///
+/// The decryption here depends on a side-channel leak by the decryption function. The leak is the
+/// error message that the padding is valid or not.
+///
+/// # Examples
+/// ```
+/// use cryptopals::set3::challenge17::*;
+/// let server = PaddingServer::new();
+/// let (iv, crypted) = server.challenge().unwrap();
+/// let plain = decrypt(&server, &iv, &crypted).unwrap();
+/// assert!(plain.starts_with("00000"));
+/// ```
+pub struct PaddingServer {
+  key: Vec<u8>,
+}
+
+impl PaddingServer {
+  pub fn new() -> PaddingServer {
+    PaddingServer { key: random::bytes(16) }
+  }
+
+  /// One of the ten lines, CBC encrypted under a fresh IV: (iv, ciphertext)
+  pub fn challenge(&self) -> Result<(Vec<u8>, Vec<u8>)> {
+    let line = rand::thread_rng().gen_range(0, LINES.len());
+    self.encrypt_line(line)
+  }
+
+  pub fn encrypt_line(&self, line: usize) -> Result<(Vec<u8>, Vec<u8>)> {
+    let plain = try!(LINES[line].from_base64());
+    let iv = random::bytes(16);
+    let crypted = try!(cbc::encrypt(&self.key, &iv, &padding::pkcs7(&plain, 16)));
+    Ok((iv, crypted))
+  }
+}
+
+impl Default for PaddingServer {
+  fn default() -> PaddingServer {
+    PaddingServer::new()
+  }
+}
+
+impl PaddingOracle for PaddingServer {
+  fn valid_padding(&self, iv: &[u8], ciphertext: &[u8]) -> Result<bool> {
+    let padded = try!(cbc::decrypt(&self.key, iv, ciphertext));
+    Ok(padding::unpad_pkcs7(&padded, 16).is_ok())
+  }
+}
+
+pub fn decrypt<O: PaddingOracle + ?Sized>(oracle: &O, iv: &[u8], crypted: &[u8]) -> Result<String> {
+  let padded = try!(::analysis::cbc::padding_oracle_decrypt(oracle, iv, crypted));
+  Ok(try!(String::from_utf8(try!(padding::unpad_pkcs7(&padded, iv.len())))))
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use serialize::base64::FromBase64;
+
+  #[test]
+  fn decrypts_every_line() {
+    let server = PaddingServer::new();
+    for (n, line) in LINES.iter().enumerate() {
+      let (iv, crypted) = server.encrypt_line(n).unwrap();
+      let expected = String::from_utf8(line.from_base64().unwrap()).unwrap();
+      assert_eq!(decrypt(&server, &iv, &crypted).unwrap(), expected);
+    }
+  }
+
+  #[test]
+  fn forges_ciphertext() {
+    use aes::cbc;
+    use analysis::cbc::padding_oracle_encrypt;
+    use padding;
+
+    let server = PaddingServer::new();
+    let message = "Forged by CBC-R; the server never saw this key".as_bytes();
+    let (iv, crypted) = padding_oracle_encrypt(&server, 16, message).unwrap();
+    assert_eq!(crypted.len(), 48);
+    assert!(server.valid_padding(&iv, &crypted).unwrap());
+    let padded = cbc::decrypt(&server.key, &iv, &crypted).unwrap();
+    assert_eq!(padding::unpad_pkcs7(&padded, 16).unwrap(), message);
+  }
+
+  #[test]
+  fn rejects_tampering() {
+    let server = PaddingServer::new();
+    let (iv, mut crypted) = server.encrypt_line(3).unwrap();
+    assert!(server.valid_padding(&iv, &crypted).unwrap());
+    let last = crypted.len() - 17;
+    crypted[last] ^= 0x5a;
+    assert!(!server.valid_padding(&iv, &crypted).unwrap());
+  }
+}
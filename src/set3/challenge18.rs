@@ -0,0 +1,39 @@
+use serialize::base64::FromBase64;
+use aes::ctr;
+use result::Result;
+
+/// Implement CTR, the stream cipher mode
+/// The string:
+///
+/// L77na/nrFsKvynd6HzOoG7GHTLXsTVu9qvY/2syLXzhPweyyMTJULu/6/kXX0KSvoOLSFQ==
+///
+/// ... decrypts to something approximating English in CTR mode, which is an AES block cipher mode
+/// that turns AES into a stream cipher, with the following parameters:
+///
+/// key=YELLOW SUBMARINE
+/// nonce=0
+/// format=64 bit unsigned little endian nonce,
+/// 64 bit little endian block count (byte count / 16)
+///
+/// CTR mode is very simple.
+///
+/// Instead of encrypting the plaintext, CTR mode encrypts a running counter, producing a 16 byte
+/// block of keystream, which is XOR'd against the plaintext.
+///
+/// CTR mode does not require padding; when you run out of plaintext, you just stop XOR'ing
+/// keystream and stop generating keystream.
+///
+/// Decryption is identical to encryption. Generate the same keystream, XOR, and recover the
+/// plaintext.
+///
+/// # Examples
+/// ```
+/// let plain = cryptopals::set3::challenge18::decrypt(
+///   "L77na/nrFsKvynd6HzOoG7GHTLXsTVu9qvY/2syLXzhPweyyMTJULu/6/kXX0KSvoOLSFQ==").unwrap();
+/// assert_eq!(plain, "Yo, VIP Let's kick it Ice, Ice, baby Ice, Ice, baby ")
+/// ```
+pub fn decrypt(b64: &str) -> Result<String> {
+  let crypted = try!(b64.from_base64());
+  let plain = try!(ctr::decrypt("YELLOW SUBMARINE".as_bytes(), 0, &crypted));
+  Ok(try!(String::from_utf8(plain)))
+}
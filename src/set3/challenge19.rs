@@ -0,0 +1,121 @@
+use serialize::base64::FromBase64;
+use result::Result;
+use super::challenge20::{self, KeystreamGuess};
+
+pub static LINES: [&str; 40] = [
+   "SSBoYXZlIG1ldCB0aGVtIGF0IGNsb3NlIG9mIGRheQ==",
+   "Q29taW5nIHdpdGggdml2aWQgZmFjZXM=",
+   "RnJvbSBjb3VudGVyIG9yIGRlc2sgYW1vbmcgZ3JleQ==",
+   "RWlnaHRlZW50aC1jZW50dXJ5IGhvdXNlcy4=",
+   "SSBoYXZlIHBhc3NlZCB3aXRoIGEgbm9kIG9mIHRoZSBoZWFk",
+   "T3IgcG9saXRlIG1lYW5pbmdsZXNzIHdvcmRzLA==",
+   "T3IgaGF2ZSBsaW5nZXJlZCBhd2hpbGUgYW5kIHNhaWQ=",
+   "UG9saXRlIG1lYW5pbmdsZXNzIHdvcmRzLA==",
+   "QW5kIHRob3VnaHQgYmVmb3JlIEkgaGFkIGRvbmU=",
+   "T2YgYSBtb2NraW5nIHRhbGUgb3IgYSBnaWJl",
+   "VG8gcGxlYXNlIGEgY29tcGFuaW9u",
+   "QXJvdW5kIHRoZSBmaXJlIGF0IHRoZSBjbHViLA==",
+   "QmVpbmcgY2VydGFpbiB0aGF0IHRoZXkgYW5kIEk=",
+   "QnV0IGxpdmVkIHdoZXJlIG1vdGxleSBpcyB3b3JuOg==",
+   "QWxsIGNoYW5nZWQsIGNoYW5nZWQgdXR0ZXJseTo=",
+   "QSB0ZXJyaWJsZSBiZWF1dHkgaXMgYm9ybi4=",
+   "VGhhdCB3b21hbidzIGRheXMgd2VyZSBzcGVudA==",
+   "SW4gaWdub3JhbnQgZ29vZCB3aWxsLA==",
+   "SGVyIG5pZ2h0cyBpbiBhcmd1bWVudA==",
+   "VW50aWwgaGVyIHZvaWNlIGdyZXcgc2hyaWxsLg==",
+   "V2hhdCB2b2ljZSBtb3JlIHN3ZWV0IHRoYW4gaGVycw==",
+   "V2hlbiB5b3VuZyBhbmQgYmVhdXRpZnVsLA==",
+   "U2hlIHJvZGUgdG8gaGFycmllcnM/",
+   "VGhpcyBtYW4gaGFkIGtlcHQgYSBzY2hvb2w=",
+   "QW5kIHJvZGUgb3VyIHdpbmdlZCBob3JzZS4=",
+   "VGhpcyBvdGhlciBoaXMgaGVscGVyIGFuZCBmcmllbmQ=",
+   "V2FzIGNvbWluZyBpbnRvIGhpcyBmb3JjZTs=",
+   "SGUgbWlnaHQgaGF2ZSB3b24gZmFtZSBpbiB0aGUgZW5kLA==",
+   "U28gc2Vuc2l0aXZlIGhpcyBuYXR1cmUgc2VlbWVkLA==",
+   "U28gZGFyaW5nIGFuZCBzd2VldCBoaXMgdGhvdWdodC4=",
+   "VGhpcyBvdGhlciBtYW4gSSBoYWQgZHJlYW1lZA==",
+   "QSBkcnVua2VuLCB2YWluLWdsb3Jpb3VzIGxvdXQu",
+   "SGUgaGFkIGRvbmUgbW9zdCBiaXR0ZXIgd3Jvbmc=",
+   "VG8gc29tZSB3aG8gYXJlIG5lYXIgbXkgaGVhcnQs",
+   "WWV0IEkgbnVtYmVyIGhpbSBpbiB0aGUgc29uZzs=",
+   "SGUsIHRvbywgaGFzIHJlc2lnbmVkIGhpcyBwYXJ0",
+   "SW4gdGhlIGNhc3VhbCBjb21lZHk7",
+   "SGUsIHRvbywgaGFzIGJlZW4gY2hhbmdlZCBpbiBoaXMgdHVybiw=",
+   "VHJhbnNmb3JtZWQgdXR0ZXJseTo=",
+   "QSB0ZXJyaWJsZSBiZWF1dHkgaXMgYm9ybi4="];
+
+/// Break fixed-nonce CTR mode using substitutions
+/// Take your CTR encrypt/decrypt function and fix its nonce value to 0. Generate a random AES key.
+///
+/// In successive encryptions (not in one big running CTR stream), encrypt each line of the base64
+/// decodes of the following, producing multiple independent ciphertexts.
+///
+/// Because the CTR nonce wasn't randomized for each encryption, each ciphertext has been encrypted
+/// against the same keystream. This is very bad.
+///
+/// Understanding that, like most stream ciphers (including RC4, and obviously any block cipher run
+/// in CTR mode), the actual "encryption" of a byte of data boils down to a single XOR operation, it
+/// should be plain that:
+///
+/// CIPHERTEXT-BYTE XOR PLAINTEXT-BYTE = KEYSTREAM-BYTE
+///
+/// And since the keystream is the same for every ciphertext:
+///
+/// CIPHERTEXT-BYTE XOR KEYSTREAM-BYTE = PLAINTEXT-BYTE (ie, "you don't say!")
+///
+/// Attack this cryptosystem piecemeal: guess letters, use expected English language frequence to
+/// validate guesses, catch common English trigrams, and so on.
+pub fn encrypt_lines() -> Result<Vec<Vec<u8>>> {
+  let mut lines = vec![];
+  for line in LINES.iter() {
+    lines.push(try!(line.from_base64()));
+  }
+  challenge20::encrypt_lines(&lines)
+}
+
+/// Starts from the statistical break, then substitutes a guess for each line that runs past the
+/// common length, so the whole of every line can be read.
+pub fn solve(crypts: &[Vec<u8>], guesses: &[(usize, &str)]) -> Result<KeystreamGuess> {
+  let mut guess = try!(challenge20::break_fixed_nonce(crypts));
+  for &(line, text) in guesses {
+    guess.crib(&crypts[line], 0, text.as_bytes());
+  }
+  Ok(guess)
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use serialize::base64::FromBase64;
+
+  fn right_bytes(guess: &KeystreamGuess,
+                 crypts: &[Vec<u8>],
+                 plains: &[Vec<u8>],
+                 len: usize)
+                 -> usize {
+    crypts.iter()
+      .zip(plains)
+      .map(|(c, p)| guess.decrypt(&c[0..len]).iter().zip(p).filter(|&(g, p)| g == p).count())
+      .sum()
+  }
+
+  #[test]
+  fn statistics_then_substitution() {
+    let crypts = encrypt_lines().unwrap();
+    let plains: Vec<Vec<u8>> = LINES.iter().map(|l| l.from_base64().unwrap()).collect();
+
+    let guess = solve(&crypts, &[]).unwrap();
+    assert_eq!(guess.columns.len(), 38);
+    assert!(right_bytes(&guess, &crypts, &plains, 20) * 4 >= 20 * 40 * 3);
+    let weak = guess.weak_columns(10);
+    assert!(!weak.is_empty());
+    assert!(weak.iter().all(|&c| c >= 20));
+
+    let longest = "He, too, has been changed in his turn,";
+    let guess = solve(&crypts, &[(37, longest)]).unwrap();
+    assert_eq!(guess.weak_columns(40), Vec::<usize>::new());
+    for (c, p) in crypts.iter().zip(plains.iter()) {
+      assert_eq!(&guess.decrypt(c), p);
+    }
+  }
+}
@@ -0,0 +1,191 @@
+use byte_convert::open_base64_lines_path;
+use result::{Result, CrackError};
+use set1::challenge6::scored_key_for_slice;
+use xor;
+use aes::ctr;
+use random;
+
+/// Break fixed-nonce CTR statistically
+/// In this file find a similar set of Base64'd plaintext. Do with them exactly what you did with
+/// the first, but solve the problem differently.
+///
+/// Instead of making spot guesses at to known plaintext, treat the collection of ciphertexts the
+/// same way you would repeating-key XOR.
+///
+/// Obviously, CTR encryption appears different from repeated-key XOR, but with a fixed nonce they
+/// are effectively the same thing.
+///
+/// To exploit this: take your collection of ciphertexts and truncate them to a common length (the
+/// length of the smallest ciphertext will work).
+///
+/// Solve the resulting concatenation of ciphertexts as if for repeating- key XOR, with a key size
+/// of the length of the ciphertext you XOR'd.
+///
+/// # Examples
+/// ```
+/// # use cryptopals::set3::challenge20;
+/// let plain = challenge20::decrypt_file("s3c20.txt").unwrap();
+/// assert_eq!(plain.len(), 60);
+/// assert!(plain[0].contains("this is a warning"));
+/// ```
+pub fn decrypt_file(path: &str) -> Result<Vec<String>> {
+  let crypts = try!(encrypt_lines(&try!(open_base64_lines_path(path))));
+  let guess = try!(break_fixed_nonce(&crypts));
+  Ok(crypts.iter().map(|c| String::from_utf8_lossy(&guess.decrypt(c)).into_owned()).collect())
+}
+
+/// Encrypts every line under the same random key, all with nonce 0.
+pub fn encrypt_lines(lines: &[Vec<u8>]) -> Result<Vec<Vec<u8>>> {
+  let key = random::bytes(16);
+  lines.iter().map(|line| ctr::encrypt(&key, 0, line)).collect()
+}
+
+/// One byte of recovered keystream.  `score` is the frequency score it came from (lower is more
+/// English-like, meaningless once `cribbed`) and `samples` how many ciphertexts reached that far.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Column {
+  pub key: u8,
+  pub score: u32,
+  pub samples: usize,
+  pub cribbed: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct KeystreamGuess {
+  pub columns: Vec<Column>,
+}
+
+/// Treats ciphertexts under a reused CTR keystream as one repeating-key XOR ciphertext, with the
+/// shortest ciphertext's length as the key size.  Past that, each column is guessed from just the
+/// ciphertexts long enough to reach it - those trailing guesses get steadily weaker.
+pub fn break_fixed_nonce(crypts: &[Vec<u8>]) -> Result<KeystreamGuess> {
+  let shortest = try!(crypts.iter()
+    .map(|c| c.len())
+    .min()
+    .ok_or(CrackError::Str("no ciphertexts")));
+  let longest = crypts.iter().map(|c| c.len()).max().unwrap_or(0);
+  let joined: Vec<u8> = crypts.iter().flat_map(|c| c[0..shortest].iter().cloned()).collect();
+
+  let mut columns = vec![];
+  for offset in 0..shortest {
+    let (score, key) = try!(scored_key_for_slice(&joined, offset, shortest)
+      .ok_or(CrackError::Str("empty column")));
+    columns.push(Column {
+      key: key,
+      score: score,
+      samples: crypts.len(),
+      cribbed: false,
+    });
+  }
+  for offset in shortest..longest {
+    let column: Vec<u8> = crypts.iter().filter_map(|c| c.get(offset)).cloned().collect();
+    let (score, key) = try!(scored_key_for_slice(&column, 0, 1)
+      .ok_or(CrackError::Str("empty column")));
+    columns.push(Column {
+      key: key,
+      score: score,
+      samples: column.len(),
+      cribbed: false,
+    });
+  }
+  Ok(KeystreamGuess { columns: columns })
+}
+
+impl KeystreamGuess {
+  pub fn keystream(&self) -> Vec<u8> {
+    self.columns.iter().map(|c| c.key).collect()
+  }
+
+  /// Decrypts as much of `crypted` as there's keystream for.
+  pub fn decrypt(&self, crypted: &[u8]) -> Vec<u8> {
+    xor::xor_iters(crypted, &self.keystream())
+  }
+
+  /// Columns guessed from fewer than `min_samples` ciphertexts, and not since cribbed.
+  pub fn weak_columns(&self, min_samples: usize) -> Vec<usize> {
+    (0..self.columns.len())
+      .filter(|&i| self.columns[i].samples < min_samples && !self.columns[i].cribbed)
+      .collect()
+  }
+
+  /// Substitutes known plaintext: `known` is what `crypted` decrypts to at `offset`.  The
+  /// keystream there is taken as certain, and extended if need be - any gap before `offset` is
+  /// filled with placeholder columns (zero key, no samples) that `weak_columns` will report.
+  pub fn crib(&mut self, crypted: &[u8], offset: usize, known: &[u8]) {
+    let crypted = crypted.get(offset..).unwrap_or(&[]);
+    for (i, (c, p)) in crypted.iter().zip(known).enumerate() {
+      let pos = offset + i;
+      while self.columns.len() < pos {
+        self.columns.push(Column {
+          key: 0,
+          score: 0,
+          samples: 0,
+          cribbed: false,
+        });
+      }
+      let samples = self.columns.get(pos).map(|col| col.samples).unwrap_or(1);
+      let column = Column {
+        key: c ^ p,
+        score: 0,
+        samples: samples,
+        cribbed: true,
+      };
+      if pos < self.columns.len() {
+        self.columns[pos] = column;
+      } else {
+        self.columns.push(column);
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use byte_convert::open_base64_lines_path;
+
+  #[test]
+  fn breaks_the_file() {
+    let lines = open_base64_lines_path("s3c20.txt").unwrap();
+    let crypts = encrypt_lines(&lines).unwrap();
+    let guess = break_fixed_nonce(&crypts).unwrap();
+
+    // Statistics get the common-length part mostly right; a few columns of capitals and
+    // punctuation stay in doubt
+    let shortest = lines.iter().map(|line| line.len()).min().unwrap();
+    let right: usize = lines.iter()
+      .zip(&crypts)
+      .map(|(line, crypted)| {
+        let plain = guess.decrypt(&crypted[..shortest]);
+        line.iter().zip(&plain).filter(|&(a, b)| a == b).count()
+      })
+      .sum();
+    assert!(right * 20 > 17 * lines.len() * shortest,
+            "{} of {} right",
+            right,
+            lines.len() * shortest);
+  }
+
+  #[test]
+  fn crib_extends() {
+    let mut guess = KeystreamGuess { columns: vec![] };
+    guess.crib(b"\x01\x02\x03", 0, b"abc");
+    assert_eq!(guess.keystream(), vec![b'a' ^ 1, b'b' ^ 2, b'c' ^ 3]);
+    assert_eq!(guess.weak_columns(10), Vec::<usize>::new());
+    assert_eq!(guess.decrypt(b"\x01\x02\x03\x04"), b"abc".to_vec());
+  }
+
+  #[test]
+  fn crib_past_the_end() {
+    let mut guess = KeystreamGuess { columns: vec![] };
+    guess.crib(b"\x01\x02", 0, b"ab");
+    guess.crib(b"\x01\x02\x03\x04\x05", 4, b"e");
+    assert_eq!(guess.columns.len(), 5);
+    assert_eq!(guess.keystream()[4], b'e' ^ 5);
+    assert_eq!(guess.weak_columns(1), vec![2, 3]);
+    assert_eq!(guess.decrypt(b"\x01\x02\x00\x00\x05")[4], b'e');
+
+    guess.crib(b"\x01", 9, b"z");
+    assert_eq!(guess.columns.len(), 5);
+  }
+}
@@ -0,0 +1,18 @@
+use rand::Rng;
+use random::Mt19937;
+
+/// Implement the MT19937 Mersenne Twister RNG
+/// You can get the psuedocode for this from Wikipedia.
+///
+/// If you're writing in Python, Ruby, or (gah) PHP, your language is probably already giving you
+/// MT19937 as "rand()"; don't use rand(). Write the RNG yourself.
+///
+/// # Examples
+/// ```
+/// let outs = cryptopals::set3::challenge21::outputs(5489, 2);
+/// assert_eq!(outs, vec![3499211612, 581869302]);
+/// ```
+pub fn outputs(seed: u32, count: usize) -> Vec<u32> {
+  let mut mt = Mt19937::new(seed);
+  (0..count).map(|_| mt.next_u32()).collect()
+}
@@ -0,0 +1,96 @@
+use rand::{self, Rng};
+use analysis::mt19937::seed_search;
+use clock::{Clock, FakeClock, SystemClock};
+use random::Mt19937;
+
+/// Crack an MT19937 seed
+/// Make sure your MT19937 accepts an integer seed value. Test it (verify that you're getting the
+/// same sequence of outputs given a seed).
+///
+/// Write a routine that performs the following operation:
+///
+/// Wait a random number of seconds between, I don't know, 40 and 1000.
+/// Seeds the RNG with the current Unix timestamp
+/// Waits a random number of seconds again.
+/// Returns the first 32 bit output of the RNG.
+///
+/// You get the idea. Go get coffee while it runs. Or just simulate the passage of time, although
+/// you're missing some of the fun of this exercise if you do that.
+///
+/// From the 32 bit RNG output, discover the seed.
+///
+/// # Examples
+/// ```
+/// assert!(cryptopals::set3::challenge22::crack_simulated(1_500_000_000));
+/// ```
+pub fn seeded_output<C: Clock>(clock: &C) -> (u32, u32) {
+  let mut rng = rand::thread_rng();
+  clock.sleep(rng.gen_range(40, 1001));
+  let seed = clock.now() as u32;
+  let output = Mt19937::new(seed).next_u32();
+  clock.sleep(rng.gen_range(40, 1001));
+  (seed, output)
+}
+
+/// Looks back up to `window` seconds from now for the timestamp that seeded `output`.
+///
+/// # Examples
+/// ```
+/// use cryptopals::clock::FakeClock;
+/// use cryptopals::set3::challenge22::{seeded_output, recover_seed};
+/// let clock = FakeClock::new(1_500_000_000);
+/// let (seed, output) = seeded_output(&clock);
+/// assert_eq!(recover_seed(&clock, output, 2000), Some(seed));
+/// ```
+pub fn recover_seed<C: Clock>(clock: &C, output: u32, window: u64) -> Option<u32> {
+  let now = clock.now();
+  let earliest = now.saturating_sub(window);
+  seed_search((earliest..now + 1).rev().map(|t| t as u32),
+              |mt| mt.next_u32() == output)
+}
+
+/// Runs the service and the attack against a simulated clock starting at `start`.
+pub fn crack_simulated(start: u64) -> bool {
+  let clock = FakeClock::new(start);
+  let (seed, output) = seeded_output(&clock);
+  recover_seed(&clock, output, 2000) == Some(seed)
+}
+
+/// The same, but really waiting: between 80 and 2000 seconds go by.
+///
+/// # Examples
+/// ```ignore
+/// assert!(cryptopals::set3::challenge22::crack_live());
+/// ```
+pub fn crack_live() -> bool {
+  let clock = SystemClock;
+  let (seed, output) = seeded_output(&clock);
+  recover_seed(&clock, output, 2000) == Some(seed)
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use clock::FakeClock;
+
+  #[test]
+  fn recovers_timestamp() {
+    let clock = FakeClock::new(1_478_000_000);
+    let (seed, output) = seeded_output(&clock);
+    assert!(clock.now() >= 1_478_000_080);
+    assert_eq!(recover_seed(&clock, output, 2000), Some(seed));
+  }
+
+  #[test]
+  fn window_too_small() {
+    let clock = FakeClock::new(1_478_000_000);
+    let (_, output) = seeded_output(&clock);
+    assert_eq!(recover_seed(&clock, output, 30), None);
+  }
+
+  #[test]
+  fn arbitrary_seed_space() {
+    let output = Mt19937::new(0xbeef).next_u32();
+    assert_eq!(seed_search(0..0x10000, |mt| mt.next_u32() == output), Some(0xbeef));
+  }
+}
@@ -0,0 +1,93 @@
+use rand::Rng;
+use random::Mt19937;
+use result::{Result, CrackError};
+
+/// Clone an MT19937 RNG from its output
+/// The internal state of MT19937 consists of 624 32 bit integers.
+///
+/// For each batch of 624 outputs, MT permutes that internal state. By permuting state regularly,
+/// MT19937 achieves a period of 2**19937, which is Big.
+///
+/// Each time MT19937 is tapped, an element of its internal state is subjected to a tempering
+/// function that diffuses bits through the result.
+///
+/// The tempering function is invertible; you can write an "untemper" function that takes an MT19937
+/// output and transforms it back into the corresponding element of the MT19937 state array.
+///
+/// To invert the temper transform, apply the inverse of each of the operations in the temper
+/// transform in reverse order.
+///
+/// Once you have "untemper" working, create a new MT19937 generator, tap it for 624 outputs,
+/// untemper each of them to recreate the state of the generator, and splice that state into a new
+/// instance of the MT19937 generator.
+///
+/// The new "spliced" generator should predict the values of the original.
+///
+/// # Examples
+/// ```
+/// use cryptopals::set3::challenge23::*;
+/// assert!(predicts_own_outputs(0x5eed));
+/// ```
+pub fn clone_mt(outputs: &[u32]) -> Result<Mt19937> {
+  if outputs.len() < 624 {
+    return Err(CrackError::Str("need 624 consecutive outputs"));
+  }
+  let mut state = [0u32; 624];
+  for (word, &out) in state.iter_mut().zip(&outputs[outputs.len() - 624..]) {
+    *word = Mt19937::untemper(out);
+  }
+  Ok(Mt19937::from_state(state))
+}
+
+/// Clones from bytes produced by `random::bytes`, which hands out each output as four
+/// little-endian bytes.  The bytes must start on an output boundary.
+pub fn clone_mt_from_bytes(bytes: &[u8]) -> Result<Mt19937> {
+  let outputs: Vec<u32> = bytes.chunks(4)
+    .filter(|c| c.len() == 4)
+    .map(|c| c.iter().rev().fold(0, |acc, &b| (acc << 8) | b as u32))
+    .collect();
+  clone_mt(&outputs)
+}
+
+pub fn predicts_own_outputs(seed: u32) -> bool {
+  let mut original = Mt19937::new(seed);
+  let leaked: Vec<u32> = (0..624).map(|_| original.next_u32()).collect();
+  match clone_mt(&leaked) {
+    Ok(mut clone) => (0..2000).all(|_| clone.next_u32() == original.next_u32()),
+    Err(_) => false,
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use rand::Rng;
+  use random::{self, Mt19937};
+
+  #[test]
+  fn clones_mid_stream() {
+    let mut original = Mt19937::new(99);
+    for _ in 0..300 {
+      original.next_u32();
+    }
+    let leaked: Vec<u32> = (0..624).map(|_| original.next_u32()).collect();
+    let mut clone = clone_mt(&leaked).unwrap();
+    for _ in 0..1500 {
+      assert_eq!(clone.next_u32(), original.next_u32());
+    }
+  }
+
+  #[test]
+  fn clones_from_bytes() {
+    let mut original = Mt19937::new(0xdecafbad);
+    let leaked = random::bytes_from(&mut original, 624 * 4);
+    let mut clone = clone_mt_from_bytes(&leaked).unwrap();
+    assert_eq!(random::bytes_from(&mut clone, 100),
+               random::bytes_from(&mut original, 100));
+  }
+
+  #[test]
+  fn too_few_outputs() {
+    assert!(clone_mt(&[1, 2, 3]).is_err());
+  }
+}
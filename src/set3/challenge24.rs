@@ -0,0 +1,115 @@
+use std::iter::repeat_n;
+use rand::{self, Rng};
+use analysis::mt19937::seed_search;
+use clock::Clock;
+use random::{self, Mt19937};
+use result::{Result, CrackError};
+use xor;
+
+/// Create the MT19937 stream cipher and break it
+/// You can create a trivial stream cipher out of any PRNG; use it to generate a sequence of 8 bit
+/// outputs and call those outputs a keystream. XOR each byte of plaintext with each successive
+/// byte of keystream.
+///
+/// Write the function that does this for MT19937 using a 16-bit seed. Verify that you can encrypt
+/// and decrypt properly. This code should look similar to your CTR code.
+///
+/// Use your function to encrypt a known plaintext (say, 14 consecutive 'A' characters) prefixed
+/// by a random number of random characters.
+///
+/// From the ciphertext, recover the "key" (the 16 bit seed).
+///
+/// Use the same idea to generate a random "password reset token" using MT19937 seeded from the
+/// current time.
+///
+/// Write a function to check if any given password token is actually the product of an MT19937
+/// PRNG seeded with the current time.
+///
+/// # Examples
+/// ```
+/// use cryptopals::set3::challenge24::*;
+/// let crypted = encrypt(0x1234, "attack at dawn".as_bytes());
+/// assert_eq!(encrypt(0x1234, &crypted), "attack at dawn".as_bytes());
+/// ```
+pub fn encrypt(seed: u16, input: &[u8]) -> Vec<u8> {
+  xor::xor_iters(input.iter().cloned(), random::keystream(Mt19937::new(seed as u32)))
+}
+
+pub fn decrypt(seed: u16, input: &[u8]) -> Vec<u8> {
+  encrypt(seed, input)
+}
+
+/// Encrypts `known` behind 5 to 40 random bytes, under a random 16 bit seed: (seed, ciphertext)
+pub fn prefixed_encryption(known: &[u8]) -> (u16, Vec<u8>) {
+  let seed: u16 = rand::thread_rng().gen();
+  let plain = [random::byte_range(5, 41), known.to_vec()].concat();
+  (seed, encrypt(seed, &plain))
+}
+
+/// Finds the seed given that `crypted` ends with `known`.
+pub fn recover_seed(crypted: &[u8], known: &[u8]) -> Result<u16> {
+  if known.len() > crypted.len() {
+    return Err(CrackError::Str("known plaintext is longer than the ciphertext"));
+  }
+  let tail = crypted.len() - known.len();
+  seed_search(0..0x10000, |mt| {
+      let stream: Vec<u8> = random::keystream(mt).take(crypted.len()).collect();
+      xor::xor_iters::<_, _, Vec<u8>, _>(&crypted[tail..], &stream[tail..]) == known
+    })
+    .map(|seed| seed as u16)
+    .ok_or(CrackError::Str("no 16 bit seed produces that keystream"))
+}
+
+pub fn password_token<C: Clock>(clock: &C) -> Vec<u8> {
+  random::bytes_from(&mut Mt19937::new(clock.now() as u32), 16)
+}
+
+/// If `token` came from an MT19937 seeded with a time in the last `window` seconds, returns that
+/// time.
+pub fn token_seed<C: Clock>(clock: &C, token: &[u8], window: u64) -> Option<u32> {
+  let now = clock.now();
+  seed_search((now.saturating_sub(window)..now + 1).map(|t| t as u32),
+              |mt| random::bytes_from(mt, token.len()) == token)
+}
+
+pub fn known_plaintext() -> Vec<u8> {
+  repeat_n(b'A', 14).collect()
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use clock::{Clock, FakeClock};
+  use random;
+  use xor;
+
+  #[test]
+  fn round_trip() {
+    let message = "Attack the castle gates from the high west wall.".as_bytes();
+    let crypted = encrypt(4321, message);
+    assert!(&crypted[..] != message);
+    assert_eq!(decrypt(4321, &crypted), message);
+  }
+
+  #[test]
+  fn shares_the_xor_path() {
+    let message = "Attack the castle gates".as_bytes();
+    let stream: Vec<u8> = random::keystream(Mt19937::new(77)).take(message.len()).collect();
+    assert_eq!(xor::xor_iters::<_, _, Vec<u8>, _>(message, &stream), encrypt(77, message));
+  }
+
+  #[test]
+  fn recovers_16_bit_seed() {
+    let (seed, crypted) = prefixed_encryption(&known_plaintext());
+    assert_eq!(recover_seed(&crypted, &known_plaintext()).unwrap(), seed);
+  }
+
+  #[test]
+  fn detects_time_seeded_tokens() {
+    let clock = FakeClock::new(1_478_000_000);
+    let token = password_token(&clock);
+    clock.sleep(120);
+    assert_eq!(token_seed(&clock, &token, 600), Some(1_478_000_000));
+    assert_eq!(token_seed(&clock, &random::bytes(16), 600), None);
+  }
+}
@@ -0,0 +1,105 @@
+use aes::ctr;
+use oracle::EditOracle;
+use random;
+use result::Result;
+use set1::challenge7;
+use xor;
+
+/// Break "random access read/write" AES CTR
+/// Back to CTR. Encrypt the recovered plaintext from this file (the ECB exercise) under CTR with a
+/// random key (for this exercise the key should be unknown to you, but hold on to it).
+///
+/// Now, write the code that allows you to "seek" into the ciphertext, decrypt, and re-encrypt with
+/// different plaintext. Expose this as a function, like, "edit(ciphertext, key, offset, newtext)".
+///
+/// Imagine the "edit" function was exposed to attackers by means of an API call that didn't reveal
+/// the key or the original plaintext; the attacker has the ciphertext and controls the offset and
+/// "new text".
+///
+/// Recover the original plaintext.
+///
+/// # Examples
+/// ```
+/// use cryptopals::set4::challenge25::*;
+/// let server = EditServer::new();
+/// let crypted = server.encrypt_file("s1c7.txt").unwrap();
+/// let plain = String::from_utf8(recover(&server, &crypted).unwrap()).unwrap();
+/// assert!(plain.find("Samson to Delilah").is_some())
+/// ```
+pub struct EditServer {
+  key: Vec<u8>,
+  nonce: u64,
+}
+
+impl EditServer {
+  pub fn new() -> EditServer {
+    EditServer {
+      key: random::bytes(16),
+      nonce: 0,
+    }
+  }
+
+  /// Decrypts the challenge 7 file and re-encrypts it under this server's CTR key.
+  pub fn encrypt_file(&self, path: &str) -> Result<Vec<u8>> {
+    let plain = try!(challenge7::decrypt_file(path));
+    self.encrypt(plain.as_bytes())
+  }
+
+  pub fn encrypt(&self, plain: &[u8]) -> Result<Vec<u8>> {
+    ctr::encrypt(&self.key, self.nonce, plain)
+  }
+}
+
+impl Default for EditServer {
+  fn default() -> EditServer {
+    EditServer::new()
+  }
+}
+
+impl EditOracle for EditServer {
+  fn edit(&self, ciphertext: &[u8], offset: usize, newtext: &[u8]) -> Result<Vec<u8>> {
+    let mut edited = ciphertext.to_vec();
+    try!(ctr::edit(&mut edited, &self.key, self.nonce, offset, newtext));
+    Ok(edited)
+  }
+}
+
+/// Overwriting everything with zeros gets the server to hand back the raw keystream.
+pub fn recover<O: EditOracle + ?Sized>(oracle: &O, ciphertext: &[u8]) -> Result<Vec<u8>> {
+  let keystream = try!(oracle.edit(ciphertext, 0, &vec![0u8; ciphertext.len()]));
+  Ok(xor::xor_iters(ciphertext, &keystream))
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use oracle::EditOracle;
+
+  #[test]
+  fn recovers_plaintext() {
+    let server = EditServer::new();
+    let message = "Attack the castle gates from the high west wall.".as_bytes();
+    let crypted = server.encrypt(message).unwrap();
+    assert_eq!(recover(&server, &crypted).unwrap(), message);
+  }
+
+  #[test]
+  fn edits_only_the_region() {
+    let server = EditServer::new();
+    let crypted = server.encrypt(&[0x41; 64]).unwrap();
+    let edited = server.edit(&crypted, 20, b"zz").unwrap();
+    assert_eq!(&edited[..20], &crypted[..20]);
+    assert!(edited[20..22] != crypted[20..22]);
+    assert_eq!(&edited[22..], &crypted[22..]);
+  }
+
+  #[test]
+  fn large_buffers() {
+    let server = EditServer::new();
+    let plain = vec![0x5a; 4 * 1024 * 1024 + 7];
+    let crypted = server.encrypt(&plain).unwrap();
+    let edited = server.edit(&crypted, 3 * 1024 * 1024 + 3, b"middle").unwrap();
+    assert_eq!(recover(&server, &edited).unwrap()[3 * 1024 * 1024 + 3..][..6],
+               b"middle"[..]);
+  }
+}
@@ -0,0 +1,91 @@
+use aes::ctr;
+use analysis;
+use oracle::EncryptOracle;
+use rand;
+use random;
+use result::Result;
+use set2::challenge16::{wrap_comment, has_admin};
+
+/// CTR bitflipping
+/// There are people in the world that believe that CTR resists bit flipping attacks of the kind
+/// to which CBC mode is susceptible.
+///
+/// Re-implement the CBC bitflipping exercise from earlier to use CTR mode instead of CBC mode.
+/// Inject an "admin=true" token.
+///
+/// # Examples
+/// ```
+/// use cryptopals::set4::challenge26::*;
+/// let oracle = CtrCommentOracle::new();
+/// let forged = make_admin(&oracle).unwrap();
+/// assert!(oracle.is_admin(&forged).unwrap());
+/// ```
+pub struct CtrCommentOracle {
+  key: Vec<u8>,
+  nonce: u64,
+}
+
+impl CtrCommentOracle {
+  pub fn new() -> CtrCommentOracle {
+    CtrCommentOracle {
+      key: random::bytes(16),
+      nonce: rand::random(),
+    }
+  }
+
+  pub fn is_admin(&self, crypted: &[u8]) -> Result<bool> {
+    Ok(has_admin(&try!(ctr::decrypt(&self.key, self.nonce, crypted))))
+  }
+}
+
+impl Default for CtrCommentOracle {
+  fn default() -> CtrCommentOracle {
+    CtrCommentOracle::new()
+  }
+}
+
+impl EncryptOracle for CtrCommentOracle {
+  fn encrypt(&self, input: &[u8]) -> Result<Vec<u8>> {
+    ctr::encrypt(&self.key, self.nonce, &wrap_comment(input))
+  }
+}
+
+/// Sends innocuous filler the same length as `;admin=true;` and flips it in place.
+pub fn make_admin<O: EncryptOracle + ?Sized>(oracle: &O) -> Result<Vec<u8>> {
+  let desired = b";admin=true;";
+  let filler = [b'A'; 12];
+  let start = try!(analysis::ctr::input_offset(oracle));
+  let crypted = try!(oracle.encrypt(&filler));
+  analysis::ctr::bitflip(&crypted, start, &filler, desired)
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use oracle::{EncryptOracle, EncryptFn};
+
+  #[test]
+  fn quotes_metacharacters() {
+    let oracle = CtrCommentOracle::new();
+    let crypted = oracle.encrypt(b";admin=true;").unwrap();
+    assert!(!oracle.is_admin(&crypted).unwrap());
+  }
+
+  #[test]
+  fn flips_to_admin() {
+    for _ in 0..8 {
+      let oracle = CtrCommentOracle::new();
+      let forged = make_admin(&oracle).unwrap();
+      assert!(oracle.is_admin(&forged).unwrap());
+    }
+  }
+
+  #[test]
+  fn finds_input_without_knowing_prefix() {
+    let oracle = CtrCommentOracle::new();
+    let shifted = EncryptFn(|input: &[u8]| {
+      oracle.encrypt(&[&b"some=longer;prefix"[..], input].concat())
+    });
+    assert_eq!(::analysis::ctr::input_offset(&shifted).unwrap(), 54);
+  }
+}
@@ -0,0 +1,128 @@
+use aes::cbc;
+use oracle::{EncryptOracle, DecryptOracle};
+use padding;
+use random;
+use result::{Result, CrackError};
+use xor;
+
+/// Recover the key from CBC with IV=Key
+/// Take your code from the CBC exercise and modify it so that it repurposes the key for CBC
+/// encryption as the IV.
+///
+/// Applications sometimes use the key as an IV on the auspices that both the sender and the
+/// receiver have to know the key already, and can save some space by using it as both a key and
+/// an IV.
+///
+/// Using the key as an IV is insecure; an attacker that can modify ciphertext in flight can get
+/// the receiver to decrypt a value that will reveal the key.
+///
+/// The CBC code from exercise 16 encrypts a URL string. Verify each byte of the plaintext for
+/// ASCII compliance (ie, look for high-ASCII values). Noncompliant messages should raise an
+/// exception or return an error that includes the decrypted plaintext (this happens all the time
+/// in real systems, for what it's worth).
+///
+/// Use your code to encrypt a message that is at least 3 blocks long:
+///
+/// AES-CBC(P_1, P_2, P_3) -> C_1, C_2, C_3
+///
+/// Modify the message (you are now the attacker):
+///
+/// C_1, C_2, C_3 -> C_1, 0, C_1
+///
+/// Decrypt the message (you are now the receiver) and raise the appropriate error if high-ASCII
+/// is found.
+///
+/// As the attacker, recovering the plaintext from the error, extract the key:
+///
+/// P'_1 XOR P'_3
+///
+/// # Examples
+/// ```
+/// use cryptopals::oracle::EncryptOracle;
+/// use cryptopals::set4::challenge27::*;
+/// let server = KeyAsIvServer::new();
+/// let secret = server.encrypt(b"attack at dawn").unwrap();
+/// let key = recover_key(&server).unwrap();
+/// assert_eq!(decrypt_with_key(&key, &secret).unwrap(), b"attack at dawn".to_vec());
+/// ```
+pub struct KeyAsIvServer {
+  key: Vec<u8>,
+}
+
+impl KeyAsIvServer {
+  pub fn new() -> KeyAsIvServer {
+    KeyAsIvServer { key: random::bytes(16) }
+  }
+}
+
+/// Decrypts a message from a sender that used `key` as its IV.
+pub fn decrypt_with_key(key: &[u8], crypted: &[u8]) -> Result<Vec<u8>> {
+  padding::unpad_pkcs7(&try!(cbc::decrypt(key, key, crypted)), 16)
+}
+
+impl Default for KeyAsIvServer {
+  fn default() -> KeyAsIvServer {
+    KeyAsIvServer::new()
+  }
+}
+
+impl EncryptOracle for KeyAsIvServer {
+  fn encrypt(&self, input: &[u8]) -> Result<Vec<u8>> {
+    cbc::encrypt(&self.key, &self.key, &padding::pkcs7(input, 16))
+  }
+}
+
+impl DecryptOracle for KeyAsIvServer {
+  /// Complains about high-ASCII plaintext by echoing it back in a `CrackError::NonAscii`.
+  fn decrypt(&self, input: &[u8]) -> Result<Vec<u8>> {
+    let plain = try!(decrypt_with_key(&self.key, input));
+    if plain.iter().any(|&b| b >= 0x80) {
+      return Err(CrackError::NonAscii(plain));
+    }
+    Ok(plain)
+  }
+}
+
+/// Sends `C_1, 0, C_1` followed by the rest of an honest ciphertext, so the padding still checks
+/// out, and reads the key out of the receiver's complaint.
+pub fn recover_key<O>(oracle: &O) -> Result<Vec<u8>>
+  where O: EncryptOracle + DecryptOracle + ?Sized
+{
+  let bs = 16;
+  let crypted = try!(oracle.encrypt(&[b'A'; 48]));
+  let first = &crypted[..bs];
+  let forged = [first, &[0; 16][..], first, &crypted[bs..]].concat();
+  match oracle.decrypt(&forged) {
+    Err(CrackError::NonAscii(plain)) => Ok(xor::xor_iters(&plain[..bs], &plain[2 * bs..3 * bs])),
+    Err(e) => Err(e),
+    Ok(_) => Err(CrackError::Str("receiver accepted the forged message")),
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use oracle::{EncryptOracle, DecryptOracle};
+  use result::CrackError;
+
+  #[test]
+  fn rejects_high_ascii() {
+    let server = KeyAsIvServer::new();
+    let crypted = server.encrypt(b"plain ascii").unwrap();
+    assert_eq!(server.decrypt(&crypted).unwrap(), b"plain ascii".to_vec());
+
+    let crypted = server.encrypt(b"caf\xc3\xa9").unwrap();
+    match server.decrypt(&crypted) {
+      Err(CrackError::NonAscii(plain)) => assert_eq!(plain, b"caf\xc3\xa9".to_vec()),
+      other => panic!("expected a non-ASCII error, got {:?}", other),
+    }
+  }
+
+  #[test]
+  fn recovers_key() {
+    for _ in 0..8 {
+      let server = KeyAsIvServer::new();
+      assert_eq!(recover_key(&server).unwrap(), server.key);
+    }
+  }
+}
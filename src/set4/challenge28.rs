@@ -0,0 +1,57 @@
+use hash::sha1;
+
+/// Implement a SHA-1 keyed MAC
+/// Find a SHA-1 implementation in the language you code in.
+///
+/// Write a function to authenticate a message under a secret key by using a secret-prefix MAC,
+/// which is simply:
+///
+/// SHA1(key || message)
+///
+/// Verify that you cannot tamper with the message without breaking the MAC you've produced, and
+/// that you can't produce a new MAC without knowing the secret key.
+///
+/// # Examples
+/// ```
+/// use cryptopals::set4::challenge28::*;
+/// let mac = secret_prefix_mac(b"YELLOW SUBMARINE", b"comment1=cooking%20MCs");
+/// assert!(verify(b"YELLOW SUBMARINE", b"comment1=cooking%20MCs", &mac));
+/// assert!(!verify(b"YELLOW SUBMARINE", b"comment1=cooking%20MCz", &mac));
+/// ```
+pub fn secret_prefix_mac(key: &[u8], message: &[u8]) -> Vec<u8> {
+  sha1::sha1(&[key, message].concat())
+}
+
+pub fn verify(key: &[u8], message: &[u8], mac: &[u8]) -> bool {
+  secret_prefix_mac(key, message) == mac
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use hash::sha1;
+
+  #[test]
+  fn tampering_breaks_the_mac() {
+    let key = b"YELLOW SUBMARINE";
+    let message = b"comment1=cooking%20MCs;userdata=foo";
+    let mac = secret_prefix_mac(key, message);
+    assert!(verify(key, message, &mac));
+
+    for i in 0..message.len() {
+      let mut tampered = message.to_vec();
+      tampered[i] ^= 1;
+      assert!(!verify(key, &tampered, &mac));
+    }
+    assert!(!verify(key, &message[1..], &mac));
+    assert!(!verify(key, &[&message[..], b";admin=true"].concat(), &mac));
+  }
+
+  #[test]
+  fn needs_the_key() {
+    let message = b"comment1=cooking%20MCs;userdata=foo";
+    let mac = secret_prefix_mac(b"YELLOW SUBMARINE", message);
+    assert!(!verify(b"YELLOW SUBMARINF", message, &mac));
+    assert!(mac != sha1::sha1(message));
+  }
+}
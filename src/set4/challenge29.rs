@@ -0,0 +1,142 @@
+use std::marker::PhantomData;
+use analysis::length_extension;
+use hash::{MerkleDamgard, Sha1};
+use oracle::VerifyOracle;
+use random;
+use result::Result;
+
+pub static MESSAGE: &[u8] =
+  b"comment1=cooking%20MCs;userdata=foo;comment2=%20like%20a%20pound%20of%20bacon";
+
+/// Break a SHA-1 keyed MAC using length extension
+/// Secret-prefix SHA-1 MACs are trivially breakable.
+///
+/// The attack on secret-prefix SHA1 relies on the fact that you can take the output of SHA-1 and
+/// use it as a new starting point for SHA-1, thus taking an arbitrary SHA-1 hash and "feeding it
+/// more data".
+///
+/// Since the key precedes the data in secret-prefix, any additional data you feed the SHA-1 hash
+/// in this fashion will appear to have been hashed with the secret key.
+///
+/// To carry out the attack, you'll need to account for the fact that SHA-1 is "padded" with the
+/// bit-length of the message; your forged message will need to include that padding. We call this
+/// "glue padding". The final message you actually forge will be:
+///
+/// SHA1(key || original-message || glue-padding || new-message)
+///
+/// (where the final padding on the whole constructed message is implied)
+///
+/// Note that to generate the glue padding, you'll need to know the original bit length of the
+/// message; the message itself is known to the attacker, but the secret key isn't, so you'll need
+/// to guess at it.
+///
+/// Using your SHA-1 implementation, take a secret-prefix MAC'd message and forge a variant of
+/// this message that ends with ";admin=true;".
+///
+/// # Examples
+/// ```
+/// use cryptopals::hash::Sha1;
+/// use cryptopals::oracle::VerifyOracle;
+/// use cryptopals::set4::challenge29::*;
+/// let server = MacServer::<Sha1>::new();
+/// let mac = server.sign(MESSAGE);
+/// let (forged, forged_mac) = forge_admin(&server, MESSAGE, &mac).unwrap();
+/// assert!(forged.ends_with(b";admin=true;"));
+/// assert!(server.verify(&forged, &forged_mac).unwrap());
+/// ```
+/// Signs and checks secret-prefix MACs, `H(key || message)`, under a key it keeps to itself.
+pub struct MacServer<H> {
+  key: Vec<u8>,
+  hash: PhantomData<H>,
+}
+
+impl<H: MerkleDamgard> MacServer<H> {
+  /// A server with a random key of up to 64 bytes.
+  pub fn new() -> MacServer<H> {
+    MacServer::with_key(&random::byte_range(1, 65))
+  }
+
+  pub fn with_key(key: &[u8]) -> MacServer<H> {
+    MacServer {
+      key: key.to_vec(),
+      hash: PhantomData,
+    }
+  }
+
+  pub fn sign(&self, message: &[u8]) -> Vec<u8> {
+    let mut hash = H::new();
+    hash.update(&self.key);
+    hash.update(message);
+    hash.digest()
+  }
+}
+
+impl<H: MerkleDamgard> Default for MacServer<H> {
+  fn default() -> MacServer<H> {
+    MacServer::new()
+  }
+}
+
+impl<H: MerkleDamgard> VerifyOracle for MacServer<H> {
+  fn verify(&self, message: &[u8], mac: &[u8]) -> Result<bool> {
+    Ok(self.sign(message) == mac)
+  }
+}
+
+pub fn forge_admin<O: VerifyOracle + ?Sized>(oracle: &O,
+                                             message: &[u8],
+                                             mac: &[u8])
+                                             -> Result<(Vec<u8>, Vec<u8>)> {
+  length_extension::forge::<Sha1, _>(oracle, message, mac, b";admin=true;", 128)
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use analysis::length_extension;
+  use hash::Sha1;
+  use oracle::VerifyOracle;
+  use set4::challenge28;
+
+  #[test]
+  fn extends_with_known_key_length() {
+    let key = b"YELLOW SUBMARINE";
+    let mac = challenge28::secret_prefix_mac(key, MESSAGE);
+    let (forged, forged_mac) =
+      length_extension::extend::<Sha1>(MESSAGE, &mac, key.len(), b";x").unwrap();
+    assert!(forged.starts_with(MESSAGE));
+    assert!(challenge28::verify(key, &forged, &forged_mac));
+
+    let (wrong, wrong_mac) =
+      length_extension::extend::<Sha1>(MESSAGE, &mac, key.len() + 1, b";x").unwrap();
+    assert!(!challenge28::verify(key, &wrong, &wrong_mac));
+  }
+
+  #[test]
+  fn signs_like_challenge28() {
+    let server = MacServer::<Sha1>::with_key(b"YELLOW SUBMARINE");
+    assert_eq!(server.sign(MESSAGE), challenge28::secret_prefix_mac(b"YELLOW SUBMARINE", MESSAGE));
+  }
+
+  #[test]
+  fn forges_admin() {
+    for _ in 0..4 {
+      let server = MacServer::<Sha1>::new();
+      let mac = server.sign(MESSAGE);
+      let (forged, forged_mac) = forge_admin(&server, MESSAGE, &mac).unwrap();
+      assert!(forged.ends_with(b";admin=true;"));
+      assert!(server.verify(&forged, &forged_mac).unwrap());
+    }
+  }
+
+  #[test]
+  fn short_mac() {
+    let server = MacServer::<Sha1>::new();
+    let mac = server.sign(MESSAGE);
+    assert!(forge_admin(&server, MESSAGE, &mac[..19]).is_err());
+    assert!(forge_admin(&server, MESSAGE, &[]).is_err());
+    assert!(length_extension::extend::<Sha1>(MESSAGE, &mac[..19], 16, b";x").is_err());
+    assert!(length_extension::extend::<Sha1>(MESSAGE, &[&mac[..], &[0]].concat(), 16, b";x")
+      .is_err());
+  }
+}
@@ -0,0 +1,81 @@
+use analysis::length_extension;
+use hash::md4;
+use hash::Md4;
+use oracle::VerifyOracle;
+use result::Result;
+
+/// Break an MD4 keyed MAC using length extension
+/// Second verse, same as the first, but use MD4 instead of SHA-1. Having done this attack once
+/// against SHA-1, the MD4 variant should take much less time; mostly just the time you'll spend
+/// Googling for an implementation of MD4.
+///
+/// # Examples
+/// ```
+/// use cryptopals::hash::Md4;
+/// use cryptopals::oracle::VerifyOracle;
+/// use cryptopals::set4::challenge29::{MacServer, MESSAGE};
+/// use cryptopals::set4::challenge30::*;
+/// let server = MacServer::<Md4>::new();
+/// let mac = server.sign(MESSAGE);
+/// let (forged, forged_mac) = forge_admin(&server, MESSAGE, &mac).unwrap();
+/// assert!(forged.ends_with(b";admin=true;"));
+/// assert!(server.verify(&forged, &forged_mac).unwrap());
+/// ```
+pub fn secret_prefix_mac(key: &[u8], message: &[u8]) -> Vec<u8> {
+  md4::md4(&[key, message].concat())
+}
+
+pub fn forge_admin<O: VerifyOracle + ?Sized>(oracle: &O,
+                                             message: &[u8],
+                                             mac: &[u8])
+                                             -> Result<(Vec<u8>, Vec<u8>)> {
+  length_extension::forge::<Md4, _>(oracle, message, mac, b";admin=true;", 128)
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use analysis::length_extension;
+  use hash::{Md4, Sha1, MerkleDamgard};
+  use oracle::VerifyOracle;
+  use set4::challenge29::{MacServer, MESSAGE};
+
+  #[test]
+  fn extends_with_known_key_length() {
+    let key = b"YELLOW SUBMARINE";
+    let mac = secret_prefix_mac(key, MESSAGE);
+    let (forged, forged_mac) =
+      length_extension::extend::<Md4>(MESSAGE, &mac, key.len(), b";x").unwrap();
+    assert_eq!(secret_prefix_mac(key, &forged), forged_mac);
+
+    // Same glue length as SHA-1, but the length is written little-endian
+    let sha1_glue = Sha1::padding((key.len() + MESSAGE.len()) as u64);
+    let glue = &forged[MESSAGE.len()..forged.len() - 2];
+    assert_eq!(glue.len(), sha1_glue.len());
+    assert!(glue != &sha1_glue[..]);
+  }
+
+  #[test]
+  fn signs_like_secret_prefix_mac() {
+    let server = MacServer::<Md4>::with_key(b"YELLOW SUBMARINE");
+    assert_eq!(server.sign(MESSAGE), secret_prefix_mac(b"YELLOW SUBMARINE", MESSAGE));
+  }
+
+  #[test]
+  fn forges_admin() {
+    for _ in 0..4 {
+      let server = MacServer::<Md4>::new();
+      let mac = server.sign(MESSAGE);
+      let (forged, forged_mac) = forge_admin(&server, MESSAGE, &mac).unwrap();
+      assert!(forged.ends_with(b";admin=true;"));
+      assert!(server.verify(&forged, &forged_mac).unwrap());
+    }
+  }
+
+  #[test]
+  fn sha1_sized_mac() {
+    let server = MacServer::<Md4>::new();
+    let mac = server.sign(MESSAGE);
+    assert!(forge_admin(&server, MESSAGE, &[&mac[..], &[0; 4]].concat()).is_err());
+  }
+}
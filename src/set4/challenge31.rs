@@ -0,0 +1,283 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use crypto::sha1::Sha1;
+use serialize::hex::{FromHex, ToHex};
+use analysis::timing;
+use hash::Digested;
+use hmac::hmac;
+use oracle::VerifyOracle;
+use random;
+use result::{Result, CrackError};
+
+const SHA1_LEN: usize = 20;
+
+/// Implement and break HMAC-SHA1 with an artificial timing leak
+/// The psuedocode on Wikipedia should be enough. HMAC is very easy.
+///
+/// Using the web framework of your choosing (Sinatra, web.py, whatever), write a tiny application
+/// that has a URL that takes a "file" argument and a "signature" argument, like so:
+///
+/// http://localhost:9000/test?file=foo&signature=46b4ec586117154dacd49d664e5d63fdc88efb51
+///
+/// Have the server generate an HMAC key, and then verify that the "signature" on incoming
+/// requests is valid for "file", using the "==" operator to compare the valid MAC for a file with
+/// the "signature" parameter (in other words, verify the HMAC the way any normal programmer would
+/// verify it).
+///
+/// Write a function, call it "insecure_compare", that implements the == operation by doing
+/// byte-at-a-time comparisons with early exit (ie, return false at the first non-matching byte).
+///
+/// In the loop for "insecure_compare", add a 50ms sleep (sleep 50ms after each byte).
+///
+/// Use your "insecure_compare" function to verify the HMACs on incoming requests, and test that
+/// the whole contraption works. Return a 500 if the MAC is invalid, and a 200 if it's OK.
+///
+/// Using the timing leak in this application, write a program that discovers the valid MAC for
+/// any file.
+///
+/// # Examples
+/// ```ignore
+/// use std::time::Duration;
+/// use cryptopals::set4::challenge31::*;
+/// let server = HmacServer::start(Duration::from_millis(50), 20).unwrap();
+/// let mac = break_hmac(&server.client(), b"foo", 20).unwrap();
+/// assert!(server.client().check(b"foo", &mac).unwrap());
+/// ```
+pub fn insecure_compare(a: &[u8], b: &[u8], delay: Duration) -> bool {
+  for i in 0..::std::cmp::max(a.len(), b.len()) {
+    if a.get(i) != b.get(i) {
+      return false;
+    }
+    thread::sleep(delay);
+  }
+  true
+}
+
+/// Serves `/test?file=...&signature=...` on an ephemeral loopback port until dropped.
+///
+/// The signature is HMAC-SHA1, cut down to `mac_len` bytes (1 to 20) so that tests can afford to
+/// break it.
+pub struct HmacServer {
+  addr: SocketAddr,
+  running: Arc<AtomicBool>,
+  handle: Option<JoinHandle<()>>,
+}
+
+impl HmacServer {
+  pub fn start(delay: Duration, mac_len: usize) -> Result<HmacServer> {
+    if mac_len == 0 || mac_len > SHA1_LEN {
+      return Err(CrackError::Str("MAC length must be between 1 and 20 bytes"));
+    }
+    let listener = try!(TcpListener::bind("127.0.0.1:0"));
+    let addr = try!(listener.local_addr());
+    let running = Arc::new(AtomicBool::new(true));
+    let still_running = running.clone();
+    let key = random::bytes(16);
+
+    let handle = thread::spawn(move || {
+      for stream in listener.incoming() {
+        if !still_running.load(Ordering::SeqCst) {
+          break;
+        }
+        if let Ok(stream) = stream {
+          let _ = respond(stream, &key, delay, mac_len);
+        }
+      }
+    });
+
+    Ok(HmacServer {
+      addr: addr,
+      running: running,
+      handle: Some(handle),
+    })
+  }
+
+  pub fn addr(&self) -> SocketAddr {
+    self.addr
+  }
+
+  pub fn client(&self) -> HmacClient {
+    HmacClient { addr: self.addr }
+  }
+}
+
+impl Drop for HmacServer {
+  fn drop(&mut self) {
+    self.running.store(false, Ordering::SeqCst);
+    // Wake the listener so it notices
+    let _ = TcpStream::connect(self.addr);
+    if let Some(handle) = self.handle.take() {
+      let _ = handle.join();
+    }
+  }
+}
+
+fn respond(stream: TcpStream, key: &[u8], delay: Duration, mac_len: usize) -> Result<()> {
+  let mut reader = BufReader::new(try!(stream.try_clone()));
+  let mut request_line = String::new();
+  try!(reader.read_line(&mut request_line));
+  loop {
+    let mut header = String::new();
+    if try!(reader.read_line(&mut header)) == 0 || header.trim().is_empty() {
+      break;
+    }
+  }
+
+  let status = match check_request(&request_line, key, delay, mac_len) {
+    Some(true) => "200 OK",
+    Some(false) => "500 Internal Server Error",
+    None => "400 Bad Request",
+  };
+  let mut stream = stream;
+  try!(write!(stream, "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status));
+  Ok(())
+}
+
+/// `None` if the request isn't something we understand, otherwise whether the signature is good.
+fn check_request(request_line: &str,
+                 key: &[u8],
+                 delay: Duration,
+                 mac_len: usize)
+                 -> Option<bool> {
+  let target = request_line.split(' ').nth(1).unwrap_or("");
+  if !target.starts_with("/test?") {
+    return None;
+  }
+  let (mut file, mut signature) = (None, None);
+  for pair in target["/test?".len()..].split('&') {
+    let mut kv = pair.splitn(2, '=');
+    match (kv.next(), kv.next()) {
+      (Some("file"), Some(v)) => file = percent_decode(v),
+      (Some("signature"), Some(v)) => signature = v.from_hex().ok(),
+      _ => (),
+    }
+  }
+  match (file, signature) {
+    (Some(file), Some(signature)) => {
+      let mac = hmac(Digested(Sha1::new()), key, &file);
+      Some(insecure_compare(&mac[..mac_len], &signature, delay))
+    }
+    _ => None,
+  }
+}
+
+fn percent_encode(bytes: &[u8]) -> String {
+  bytes.iter()
+    .map(|&b| match b {
+      b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'.' | b'-' | b'_' => (b as char).to_string(),
+      _ => format!("%{:02X}", b),
+    })
+    .collect()
+}
+
+fn percent_decode(text: &str) -> Option<Vec<u8>> {
+  let bytes = text.as_bytes();
+  let mut decoded = Vec::with_capacity(bytes.len());
+  let mut i = 0;
+  while i < bytes.len() {
+    if bytes[i] == b'%' {
+      match text.get(i + 1..i + 3).and_then(|hex| hex.from_hex().ok()) {
+        Some(byte) => decoded.push(byte[0]),
+        None => return None,
+      }
+      i += 3;
+    } else {
+      decoded.push(bytes[i]);
+      i += 1;
+    }
+  }
+  Some(decoded)
+}
+
+/// Talks HTTP to an `HmacServer`.
+pub struct HmacClient {
+  addr: SocketAddr,
+}
+
+impl HmacClient {
+  /// Sends one request, and reports whether the server accepted the signature.
+  pub fn check(&self, file: &[u8], signature: &[u8]) -> Result<bool> {
+    let mut stream = try!(TcpStream::connect(self.addr));
+    try!(write!(stream,
+                concat!("GET /test?file={}&signature={} HTTP/1.1\r\n",
+                        "Host: {}\r\nConnection: close\r\n\r\n"),
+                percent_encode(file),
+                signature.to_hex(),
+                self.addr));
+    let mut response = String::new();
+    try!(stream.read_to_string(&mut response));
+    match response.split(' ').nth(1) {
+      Some("200") => Ok(true),
+      Some("500") => Ok(false),
+      _ => Err(CrackError::Str("unexpected response from the HMAC server")),
+    }
+  }
+}
+
+impl VerifyOracle for HmacClient {
+  fn verify(&self, message: &[u8], mac: &[u8]) -> Result<bool> {
+    self.check(message, mac)
+  }
+}
+
+/// With 50ms a byte, one sample per guess is plenty.
+pub fn break_hmac<O: VerifyOracle + ?Sized>(oracle: &O,
+                                            file: &[u8],
+                                            mac_len: usize)
+                                            -> Result<Vec<u8>> {
+  timing::recover_mac(oracle, file, mac_len, 1)
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use std::time::Duration;
+
+  #[test]
+  fn compares_with_early_exit() {
+    let delay = Duration::from_millis(0);
+    assert!(insecure_compare(b"abc", b"abc", delay));
+    assert!(!insecure_compare(b"abc", b"abd", delay));
+    assert!(!insecure_compare(b"abc", b"ab", delay));
+  }
+
+  #[test]
+  fn percent_encoding_round_trips() {
+    let bytes: Vec<u8> = (0..256).map(|b| b as u8).collect();
+    assert_eq!(percent_decode(&percent_encode(&bytes)).unwrap(), bytes);
+    assert_eq!(percent_decode("a%2"), None);
+  }
+
+  #[test]
+  fn serves_over_loopback() {
+    let server = HmacServer::start(Duration::from_millis(0), 20).unwrap();
+    let client = server.client();
+    assert!(!client.check(b"foo", &[0; 20]).unwrap());
+    assert!(server.addr().ip().is_loopback());
+  }
+
+  #[test]
+  fn rejects_bad_mac_len() {
+    let delay = Duration::from_millis(0);
+    assert!(HmacServer::start(delay, 0).is_err());
+    assert!(HmacServer::start(delay, 21).is_err());
+  }
+
+  #[test]
+  fn rejects_zero_samples() {
+    let server = HmacServer::start(Duration::from_millis(0), 2).unwrap();
+    assert!(timing::recover_mac(&server.client(), b"foo", 2, 0).is_err());
+  }
+
+  #[test]
+  fn breaks_truncated_hmac() {
+    let server = HmacServer::start(Duration::from_millis(50), 2).unwrap();
+    let client = server.client();
+    let mac = break_hmac(&client, b"foo", 2).unwrap();
+    assert!(client.check(b"foo", &mac).unwrap());
+  }
+}
@@ -0,0 +1,96 @@
+use analysis::timing;
+use oracle::VerifyOracle;
+use result::Result;
+
+/// Break HMAC-SHA1 with a slightly less artificial timing leak
+/// Reduce the sleep in your "insecure_compare" until your previous solution breaks. (Try 5ms to
+/// start.)
+///
+/// Now break it again.
+///
+/// # Examples
+/// ```no_run
+/// use std::time::Duration;
+/// use cryptopals::set4::challenge31::HmacServer;
+/// use cryptopals::set4::challenge32::*;
+/// let server = HmacServer::start(Duration::from_millis(5), 20).unwrap();
+/// let mac = break_hmac(&server.client(), b"foo", 20).unwrap();
+/// assert!(server.client().check(b"foo", &mac).unwrap());
+/// ```
+pub fn break_hmac<O: VerifyOracle + ?Sized>(oracle: &O,
+                                            file: &[u8],
+                                            mac_len: usize)
+                                            -> Result<Vec<u8>> {
+  timing::recover_mac(oracle, file, mac_len, SAMPLES)
+}
+
+const SAMPLES: usize = 5;
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use std::cell::{Cell, RefCell};
+  use std::time::Duration;
+  use crypto::sha1::Sha1;
+  use rand::Rng;
+  use analysis::timing::{self, Timer};
+  use hash::Digested;
+  use hmac::hmac;
+  use random::Mt19937;
+  use set4::challenge31::HmacServer;
+
+  /// `insecure_compare` at 5ms a byte without the waiting: every check moves a simulated clock on
+  /// by what the comparison would have slept, plus up to 4ms of jitter and the odd 20ms stall.
+  struct SimulatedLeak {
+    mac: Vec<u8>,
+    now: Cell<u64>,
+    rng: RefCell<Mt19937>,
+  }
+
+  impl VerifyOracle for SimulatedLeak {
+    fn verify(&self, _: &[u8], mac: &[u8]) -> Result<bool> {
+      let matched = self.mac.iter().zip(mac).take_while(|&(a, b)| a == b).count() as u64;
+      let mut rng = self.rng.borrow_mut();
+      let jitter = rng.gen_range(0, 4_000_000);
+      let stall = if rng.gen_range(0, 100) == 0 { 20_000_000 } else { 0 };
+      self.now.set(self.now.get() + matched * 5_000_000 + jitter + stall);
+      Ok(mac == &self.mac[..])
+    }
+  }
+
+  impl Timer for SimulatedLeak {
+    fn nanos(&self) -> u64 {
+      self.now.get()
+    }
+  }
+
+  #[test]
+  fn breaks_full_hmac_simulated() {
+    let leak = SimulatedLeak {
+      mac: hmac(Digested(Sha1::new()), b"YELLOW SUBMARINE", b"foo"),
+      now: Cell::new(0),
+      rng: RefCell::new(Mt19937::new(5489)),
+    };
+    let mac = timing::recover_mac_with(&leak, &leak, b"foo", 20, SAMPLES).unwrap();
+    assert_eq!(mac, leak.mac);
+  }
+
+  #[test]
+  fn breaks_truncated_hmac() {
+    let server = HmacServer::start(Duration::from_millis(5), 3).unwrap();
+    let client = server.client();
+    let mac = break_hmac(&client, b"foo", 3).unwrap();
+    assert!(client.check(b"foo", &mac).unwrap());
+  }
+
+  // The whole 20 bytes at 5ms over a real socket takes tens of minutes, so this only runs when
+  // asked for; breaks_full_hmac_simulated covers the same attack in moments
+  #[test]
+  #[ignore]
+  fn breaks_full_hmac() {
+    let server = HmacServer::start(Duration::from_millis(5), 20).unwrap();
+    let client = server.client();
+    let mac = break_hmac(&client, b"foo", 20).unwrap();
+    assert!(client.check(b"foo", &mac).unwrap());
+  }
+}
@@ -0,0 +1,8 @@
+pub mod challenge25;
+pub mod challenge26;
+pub mod challenge27;
+pub mod challenge28;
+pub mod challenge29;
+pub mod challenge30;
+pub mod challenge31;
+pub mod challenge32;
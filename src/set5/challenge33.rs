@@ -0,0 +1,61 @@
+use num::ToPrimitive;
+use rand::Rng;
+use dh::{self, Group};
+
+/// Implement Diffie-Hellman
+/// For one of the most important algorithms in cryptography this exercise couldn't be a whole lot
+/// easier.
+///
+/// Set a variable "p" to 37 and "g" to 5. This algorithm is so easy I'm not even going to explain
+/// it. Just do what I do.
+///
+/// Generate "a", a random number mod 37. Now generate "A", which is "g" raised to the "a" power
+/// mode 37 --- A = (g**a) % p.
+///
+/// Do the same for "b" and "B".
+///
+/// "A" and "B" are public keys. Generate a session key with them; set "s" to "A" raised to the
+/// "b" power mod 37 --- s = (A**b) % p.
+///
+/// Do the same with "B" and "a".
+///
+/// Ensure that the two "s" values are the same.
+///
+/// Now, ensure you can do this with bignums, with the NIST prime and g = 2. This is very easy to
+/// do in Python or Ruby or other high-level languages that auto-promote fixnums to bignums, but it
+/// isn't "hard" anywhere.
+///
+/// Note that you'll need to write your own modexp (this is blackboard math, don't freak out),
+/// because you'll blow out your bignum library raising "a" to the 1024-bit-numberth power. You
+/// can find modexp routines on Rosetta Code for most languages.
+///
+/// Turn "s" into a key by hashing it (SHA-1 is fine, and we'll use SHA-1 later in these
+/// exercises) and you're done.
+///
+/// # Examples
+/// ```
+/// extern crate rand;
+/// extern crate cryptopals;
+/// use cryptopals::set5::challenge33::*;
+/// # fn main() {
+/// let mut rng = rand::thread_rng();
+/// let (alice, bob) = toy_exchange(&mut rng);
+/// assert_eq!(alice, bob);
+/// let (alice, bob) = nist_exchange(&mut rng);
+/// assert_eq!(alice.len(), 16);
+/// assert_eq!(alice, bob);
+/// # }
+/// ```
+pub fn toy_exchange<R: Rng>(rng: &mut R) -> (u32, u32) {
+  let group = Group::new(37u32.into(), 5u32.into());
+  let (a, b) = (group.keypair(rng), group.keypair(rng));
+  let (s_a, s_b) = (a.shared_secret(b.public()), b.shared_secret(a.public()));
+  (s_a.to_u32().unwrap(), s_b.to_u32().unwrap())
+}
+
+/// Both sides' AES keys after an exchange over the NIST group.
+pub fn nist_exchange<R: Rng>(rng: &mut R) -> (Vec<u8>, Vec<u8>) {
+  let group = Group::nist();
+  let (a, b) = (group.keypair(rng), group.keypair(rng));
+  (dh::derive_key(&a.shared_secret(b.public())), dh::derive_key(&b.shared_secret(a.public())))
+}
@@ -0,0 +1,303 @@
+use std::sync::mpsc::{self, Sender, Receiver};
+use std::thread;
+use num::Zero;
+use num_bigint::BigUint;
+use rand;
+use aes::cbc;
+use dh::{self, Group};
+use padding;
+use random;
+use result::{Result, CrackError};
+
+/// What goes over the wire between Alice and Bob (and whoever sits in between).
+#[derive(Debug, Clone)]
+pub enum Message {
+  /// A group proposed, or the one agreed to, when the group is negotiated first
+  Group(Group),
+  /// p, g and the sender's public key
+  Params(Group, BigUint),
+  Public(BigUint),
+  /// AES-CBC ciphertext with its IV tacked on the end
+  Data(Vec<u8>),
+}
+
+/// One end of a two-way link: what's sent here is received at the other end.
+pub struct Channel {
+  tx: Sender<Message>,
+  rx: Receiver<Message>,
+}
+
+impl Channel {
+  pub fn pair() -> (Channel, Channel) {
+    let (a_tx, b_rx) = mpsc::channel();
+    let (b_tx, a_rx) = mpsc::channel();
+    (Channel { tx: a_tx, rx: a_rx }, Channel { tx: b_tx, rx: b_rx })
+  }
+
+  pub fn send(&self, message: Message) -> Result<()> {
+    self.tx.send(message).map_err(|_| CrackError::Str("the other end hung up"))
+  }
+
+  pub fn recv(&self) -> Result<Message> {
+    self.rx.recv().map_err(|_| CrackError::Str("the other end hung up"))
+  }
+
+  pub fn recv_public(&self) -> Result<BigUint> {
+    match try!(self.recv()) {
+      Message::Public(public) => Ok(public),
+      _ => Err(CrackError::Str("expected a public key")),
+    }
+  }
+
+  pub fn recv_data(&self) -> Result<Vec<u8>> {
+    match try!(self.recv()) {
+      Message::Data(data) => Ok(data),
+      _ => Err(CrackError::Str("expected data")),
+    }
+  }
+}
+
+/// Encrypts under a fresh IV, which goes on the end.
+pub fn seal(key: &[u8], plain: &[u8]) -> Result<Vec<u8>> {
+  let iv = random::bytes(16);
+  let crypted = try!(cbc::encrypt(key, &iv, &padding::pkcs7(plain, 16)));
+  Ok([crypted, iv].concat())
+}
+
+pub fn open(key: &[u8], sealed: &[u8]) -> Result<Vec<u8>> {
+  if sealed.len() < 32 {
+    return Err(CrackError::Str("sealed message is too short"));
+  }
+  let (crypted, iv) = sealed.split_at(sealed.len() - 16);
+  padding::unpad_pkcs7(&try!(cbc::decrypt(key, iv, crypted)), 16)
+}
+
+/// Agrees a key with Bob, then sends each message and checks that Bob echoes it back.  Returns
+/// the echoes.
+pub fn alice(chan: &Channel, group: &Group, messages: &[Vec<u8>]) -> Result<Vec<Vec<u8>>> {
+  let keys = group.keypair(&mut rand::thread_rng());
+  try!(chan.send(Message::Params(group.clone(), keys.public().clone())));
+  let key = dh::derive_key(&keys.shared_secret(&try!(chan.recv_public())));
+  send_echoes(chan, &key, messages)
+}
+
+/// Alice's half of the conversation once there's a key.
+pub fn send_echoes(chan: &Channel, key: &[u8], messages: &[Vec<u8>]) -> Result<Vec<Vec<u8>>> {
+  let mut echoes = vec![];
+  for message in messages {
+    try!(chan.send(Message::Data(try!(seal(key, message)))));
+    let echo = try!(open(key, &try!(chan.recv_data())));
+    if &echo != message {
+      return Err(CrackError::Str("Bob's echo doesn't match"));
+    }
+    echoes.push(echo);
+  }
+  Ok(echoes)
+}
+
+/// Takes whatever group Alice proposes, then echoes everything back under a new IV until she
+/// hangs up.  Returns what he was sent.
+pub fn bob(chan: &Channel) -> Result<Vec<Vec<u8>>> {
+  let (group, other) = match try!(chan.recv()) {
+    Message::Params(group, public) => (group, public),
+    _ => return Err(CrackError::Str("expected DH parameters")),
+  };
+  let keys = group.keypair(&mut rand::thread_rng());
+  try!(chan.send(Message::Public(keys.public().clone())));
+  echo(chan, &dh::derive_key(&keys.shared_secret(&other)))
+}
+
+/// Bob's half of the conversation once there's a key.
+pub fn echo(chan: &Channel, key: &[u8]) -> Result<Vec<Vec<u8>>> {
+  let mut received = vec![];
+  while let Ok(Message::Data(data)) = chan.recv() {
+    let message = try!(open(key, &data));
+    try!(chan.send(Message::Data(try!(seal(key, &message)))));
+    received.push(message);
+  }
+  Ok(received)
+}
+
+/// Passes data back and forth until Alice hangs up, opening everything with `key` on the way.
+pub fn relay(alice: &Channel, bob: &Channel, key: &[u8]) -> Result<Vec<Vec<u8>>> {
+  let mut overheard = vec![];
+  while let Ok(Message::Data(data)) = alice.recv() {
+    overheard.push(try!(open(key, &data)));
+    try!(bob.send(Message::Data(data)));
+    let reply = try!(bob.recv_data());
+    overheard.push(try!(open(key, &reply)));
+    try!(alice.send(Message::Data(reply)));
+  }
+  Ok(overheard)
+}
+
+/// Hands each side `p` in place of the other's public key, so both secrets are `p^x mod p = 0`.
+pub fn key_fixing(alice: &Channel, bob: &Channel) -> Result<Vec<Vec<u8>>> {
+  let group = match try!(alice.recv()) {
+    Message::Params(group, _) => group,
+    _ => return Err(CrackError::Str("expected DH parameters")),
+  };
+  try!(bob.send(Message::Params(group.clone(), group.p.clone())));
+  try!(bob.recv_public());
+  try!(alice.send(Message::Public(group.p.clone())));
+  relay(alice, bob, &dh::derive_key(&BigUint::zero()))
+}
+
+/// What each party ended up with after a conversation.
+pub struct Transcript {
+  pub alice: Vec<Vec<u8>>,
+  pub bob: Vec<Vec<u8>>,
+  pub mallory: Vec<Vec<u8>>,
+}
+
+/// A man in the middle, for when there isn't one: `None::<Eavesdropper>`.
+pub type Eavesdropper = fn(&Channel, &Channel) -> Result<Vec<Vec<u8>>>;
+
+fn join<T>(handle: thread::JoinHandle<Result<T>>) -> Result<T> {
+  match handle.join() {
+    Ok(result) => result,
+    Err(_) => Err(CrackError::Str("a party to the conversation panicked")),
+  }
+}
+
+/// Runs Alice and Bob on their own threads, with `mallory` (if any) on a third between them.
+pub fn converse<A, B, M>(alice: A, bob: B, mallory: Option<M>) -> Result<Transcript>
+  where A: FnOnce(&Channel) -> Result<Vec<Vec<u8>>> + Send + 'static,
+        B: FnOnce(&Channel) -> Result<Vec<Vec<u8>>> + Send + 'static,
+        M: FnOnce(&Channel, &Channel) -> Result<Vec<Vec<u8>>> + Send + 'static
+{
+  let (alice_end, far_end) = Channel::pair();
+  let (mallory_handle, bob_end) = match mallory {
+    Some(mallory) => {
+      let (near_bob, bob_end) = Channel::pair();
+      (Some(thread::spawn(move || mallory(&far_end, &near_bob))), bob_end)
+    }
+    None => (None, far_end),
+  };
+  let bob_handle = thread::spawn(move || bob(&bob_end));
+  // Alice hanging up is what tells everyone else the conversation is over
+  let alice_handle = thread::spawn(move || alice(&alice_end));
+
+  let alice = try!(join(alice_handle));
+  let mallory = match mallory_handle {
+    Some(handle) => try!(join(handle)),
+    None => vec![],
+  };
+  Ok(Transcript {
+    alice: alice,
+    bob: try!(join(bob_handle)),
+    mallory: mallory,
+  })
+}
+
+/// Implement a MITM key-fixing attack on Diffie-Hellman with parameter injection
+/// Use the code you just worked out to build a protocol and an "echo" bot. You don't actually
+/// have to do the network part of this if you don't want; just simulate that. The protocol is:
+///
+/// A->B
+///     Send "p", "g", "A"
+/// B->A
+///     Send "B"
+/// A->B
+///     Send AES-CBC(SHA1(s)[0:16], iv=random(16), msg) + iv
+/// B->A
+///     Send AES-CBC(SHA1(s)[0:16], iv=random(16), A's msg) + iv
+///
+/// (In other words, derive an AES key from DH with SHA1, use it in both directions, and do CBC
+/// with random IVs appended or prepended to the message).
+///
+/// Now implement the following MITM attack:
+///
+/// A->M
+///     Send "p", "g", "A"
+/// M->B
+///     Send "p", "g", "p"
+/// B->M
+///     Send "B"
+/// M->A
+///     Send "p"
+/// A->M
+///     Send AES-CBC(SHA1(s)[0:16], iv=random(16), msg) + iv
+/// M->B
+///     Relay that to B
+/// B->M
+///     Send AES-CBC(SHA1(s)[0:16], iv=random(16), A's msg) + iv
+/// M->A
+///     Relay that to A
+///
+/// M should be able to decrypt the messages. "A" and "B" in the protocol --- the public keys,
+/// over the wire --- have been swapped out with "p". Do the DH math on this quickly to see what
+/// that does to the predictability of the key.
+///
+/// Decrypt the messages from M's vantage point as they go by.
+///
+/// Note that you don't actually have to inject bogus parameters to make this attack work; you
+/// could just generate Ma, MA, Mb, and MB as valid DH parameters to do a generic MITM attack. But
+/// do the parameter injection attack; it's going to come up again.
+///
+/// # Examples
+/// ```
+/// use cryptopals::set5::challenge34::*;
+/// let messages = vec![b"hello bob".to_vec(), b"attack at dawn".to_vec()];
+/// let transcript = eavesdrop(messages.clone()).unwrap();
+/// assert_eq!(transcript.alice, messages);
+/// assert_eq!(transcript.bob, messages);
+/// assert_eq!(transcript.mallory,
+///            messages.iter().flat_map(|m| vec![m.clone(), m.clone()]).collect::<Vec<_>>());
+/// ```
+pub fn eavesdrop(messages: Vec<Vec<u8>>) -> Result<Transcript> {
+  converse(move |chan: &Channel| alice(chan, &Group::nist(), &messages),
+           bob,
+           Some(key_fixing))
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+  use super::*;
+  use dh::Group;
+
+  /// What Alice sends in the tests here and in challenge 35, an empty message included.
+  pub(crate) fn messages() -> Vec<Vec<u8>> {
+    vec![b"hello bob".to_vec(),
+         b"the quick brown fox jumps over the lazy dog".to_vec(),
+         vec![],
+         b"YELLOW SUBMARINE".to_vec()]
+  }
+
+  /// Alice got every message echoed and Bob received every one, while Mallory - if she was
+  /// `listening` - overheard each of them twice, once either way.
+  pub(crate) fn assert_relayed(transcript: &Transcript, listening: bool) {
+    assert_eq!(transcript.alice, messages());
+    assert_eq!(transcript.bob, messages());
+    let overheard: Vec<Vec<u8>> = if listening {
+      messages().into_iter().flat_map(|m| vec![m.clone(), m]).collect()
+    } else {
+      vec![]
+    };
+    assert_eq!(transcript.mallory, overheard);
+  }
+
+  #[test]
+  fn honest_echo() {
+    let sent = messages();
+    let transcript = converse(move |chan: &Channel| alice(chan, &Group::nist(), &sent),
+                              bob,
+                              None::<Eavesdropper>)
+      .unwrap();
+    assert_relayed(&transcript, false);
+  }
+
+  #[test]
+  fn key_fixing_reads_everything() {
+    assert_relayed(&eavesdrop(messages()).unwrap(), true);
+  }
+
+  #[test]
+  fn seals_with_trailing_iv() {
+    let key = b"YELLOW SUBMARINE";
+    let sealed = seal(key, b"hi").unwrap();
+    assert_eq!(sealed.len(), 32);
+    assert_eq!(open(key, &sealed).unwrap(), b"hi".to_vec());
+    assert!(open(key, &sealed[..16]).is_err());
+  }
+}
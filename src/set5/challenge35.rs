@@ -0,0 +1,173 @@
+use num::{One, Zero};
+use num_bigint::BigUint;
+use rand;
+use dh::{self, Group};
+use result::{Result, CrackError};
+use super::challenge34::{Channel, Message, Transcript, converse, send_echoes, echo, relay};
+
+/// Implement DH with negotiated groups, and break with malicious "g" parameters
+/// A->B
+///     Send "p", "g"
+/// B->A
+///     Send ACK
+/// A->B
+///     Send "A"
+/// B->A
+///     Send "B"
+/// A->B
+///     Send AES-CBC(SHA1(s)[0:16], iv=random(16), msg) + iv
+/// B->A
+///     Send AES-CBC(SHA1(s)[0:16], iv=random(16), A's msg) + iv
+///
+/// Do the MITM attack again, but play with "g". What happens with:
+///
+/// g = 1
+/// g = p
+/// g = p - 1
+///
+/// Write attacks for each.
+///
+/// When does this ever happen? Honestly, not that often in real-world systems. If you can mess
+/// with "g", chances are you can mess with something worse. Most systems pre-agree on a static DH
+/// group. But the same construction exists in Elliptic Curve Diffie-Hellman, and this becomes
+/// more relevant there.
+///
+/// # Examples
+/// ```
+/// use cryptopals::set5::challenge35::*;
+/// let messages = vec![b"hello bob".to_vec(), b"attack at dawn".to_vec()];
+/// for &injection in &[Injection::One, Injection::P, Injection::PMinusOne] {
+///   let transcript = eavesdrop(injection, messages.clone()).unwrap();
+///   assert_eq!(transcript.bob, messages);
+///   assert_eq!(transcript.mallory.len(), 4);
+///   assert_eq!(transcript.mallory[2], messages[1]);
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Injection {
+  One,
+  P,
+  PMinusOne,
+}
+
+impl Injection {
+  pub fn generator(&self, p: &BigUint) -> BigUint {
+    match *self {
+      Injection::One => BigUint::one(),
+      Injection::P => p.clone(),
+      Injection::PMinusOne => p - BigUint::one(),
+    }
+  }
+
+  /// Every shared secret the forced generator can lead to: `1^ab = 1` and `p^ab = 0`, but
+  /// `(p-1)^ab` is `p-1` when `ab` is odd and `1` when it's even.
+  pub fn candidate_secrets(&self, p: &BigUint) -> Vec<BigUint> {
+    match *self {
+      Injection::One => vec![BigUint::one()],
+      Injection::P => vec![BigUint::zero()],
+      Injection::PMinusOne => vec![BigUint::one(), p - BigUint::one()],
+    }
+  }
+}
+
+/// Proposes `group`, then uses whatever group Bob acknowledges.
+pub fn alice(chan: &Channel, group: &Group, messages: &[Vec<u8>]) -> Result<Vec<Vec<u8>>> {
+  try!(chan.send(Message::Group(group.clone())));
+  let group = match try!(chan.recv()) {
+    Message::Group(group) => group,
+    _ => return Err(CrackError::Str("expected the agreed group")),
+  };
+  let keys = group.keypair(&mut rand::thread_rng());
+  try!(chan.send(Message::Public(keys.public().clone())));
+  let key = dh::derive_key(&keys.shared_secret(&try!(chan.recv_public())));
+  send_echoes(chan, &key, messages)
+}
+
+/// Accepts and acknowledges whatever group is proposed.
+pub fn bob(chan: &Channel) -> Result<Vec<Vec<u8>>> {
+  let group = match try!(chan.recv()) {
+    Message::Group(group) => group,
+    _ => return Err(CrackError::Str("expected a group proposal")),
+  };
+  try!(chan.send(Message::Group(group.clone())));
+  let other = try!(chan.recv_public());
+  let keys = group.keypair(&mut rand::thread_rng());
+  try!(chan.send(Message::Public(keys.public().clone())));
+  echo(chan, &dh::derive_key(&keys.shared_secret(&other)))
+}
+
+/// Swaps in a generator that makes the secret predictable, and lets the negotiation carry it to
+/// both sides.
+pub fn malicious_generator(injection: Injection,
+                           alice: &Channel,
+                           bob: &Channel)
+                           -> Result<Vec<Vec<u8>>> {
+  let mut group = match try!(alice.recv()) {
+    Message::Group(group) => group,
+    _ => return Err(CrackError::Str("expected a group proposal")),
+  };
+  group.g = injection.generator(&group.p);
+  try!(bob.send(Message::Group(group.clone())));
+  try!(alice.send(try!(bob.recv())));
+
+  let a_public = try!(alice.recv_public());
+  try!(bob.send(Message::Public(a_public.clone())));
+  let b_public = try!(bob.recv_public());
+  try!(alice.send(Message::Public(b_public.clone())));
+
+  // With g = p - 1 each public key is p - 1 if its private key was odd and 1 otherwise, and the
+  // secret is p - 1 only if both were odd
+  let candidates = injection.candidate_secrets(&group.p);
+  let secret = if candidates.len() > 1 && a_public == candidates[1] && b_public == candidates[1] {
+    candidates[1].clone()
+  } else {
+    candidates[0].clone()
+  };
+  relay(alice, bob, &dh::derive_key(&secret))
+}
+
+pub fn eavesdrop(injection: Injection, messages: Vec<Vec<u8>>) -> Result<Transcript> {
+  converse(move |chan: &Channel| alice(chan, &Group::nist(), &messages),
+           bob,
+           Some(move |a: &Channel, b: &Channel| malicious_generator(injection, a, b)))
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use num_bigint::BigUint;
+  use dh::Group;
+  use set5::challenge34::{Channel, Eavesdropper};
+  use set5::challenge34::test::{messages, assert_relayed};
+
+  #[test]
+  fn honest_negotiation() {
+    let sent = messages();
+    let transcript = converse(move |chan: &Channel| alice(chan, &Group::nist(), &sent),
+                              bob,
+                              None::<Eavesdropper>)
+      .unwrap();
+    assert_relayed(&transcript, false);
+  }
+
+  #[test]
+  fn forced_generators() {
+    for _ in 0..4 {
+      for &injection in &[Injection::One, Injection::P, Injection::PMinusOne] {
+        assert_relayed(&eavesdrop(injection, messages()).unwrap(), true);
+      }
+    }
+  }
+
+  #[test]
+  fn candidates_cover_tiny_group() {
+    let group = Group::new(BigUint::from(37u32), BigUint::from(36u32));
+    for a in 1..37u32 {
+      for b in 1..37u32 {
+        let secret = group.keypair_from(BigUint::from(a))
+          .shared_secret(group.keypair_from(BigUint::from(b)).public());
+        assert!(Injection::PMinusOne.candidate_secrets(&group.p).contains(&secret));
+      }
+    }
+  }
+}
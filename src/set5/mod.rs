@@ -0,0 +1,3 @@
+pub mod challenge33;
+pub mod challenge34;
+pub mod challenge35;